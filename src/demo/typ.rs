@@ -5,6 +5,24 @@ use std::error::Error;
 use std::path::PathBuf;
 
 use crate::{dx::*, ext::*, ext::tdx::*, demo};
+use crate::ext::render::{RenderState, RenderStack};
+use crate::ext::anim::{Animation, Clip, PlayMode};
+use crate::ext::batch::Batch2D;
+use crate::ext::hlsl::Hlsl;
+use crate::ext::program::ProgramDesc;
+use crate::ext::constants::ShaderConstants;
+
+crate::cbuffer! {
+  /// CamLight (cb_CamLight slot 8 in shader_PS.hlsl)
+  pub struct CamLight {
+    /// cam_pos4
+    pub cam_pos4: FLOAT4,
+    /// cam_lat4
+    pub cam_lat4: FLOAT4,
+    /// light ratio: r[0].xyzw and r[1].xy, camera angle: r[1].w
+    pub r: [FLOAT4; 2]
+  }
+}
 
 /// for DX11
 pub fn proc_cb(t: &str, b_cb: &[FLOAT4]) {
@@ -17,10 +35,10 @@ pub fn proc_cb(t: &str, b_cb: &[FLOAT4]) {
 /// for DX9
 pub fn proc_sh(sh: &impl Ts, ns: &[&str]) {
   for n in ns {
-    let cnt = sh.get_const_count_to_shader(n);
-    let arr = sh.get_const_default_param_f_to_shader(n);
-    let idx = sh.get_const_index_to_shader(n);
-    println!("sh [{:?}] {}, {:?}, {}", n, cnt, arr, idx);
+    match ShaderConstants::new(sh, &[n]) {
+    Err(e) => println!("{}", e),
+    Ok(sc) => println!("sh [{:?}] {:?}", n, sc.get(n))
+    }
   }
 }
 
@@ -104,6 +122,8 @@ pub fn screen(p: &str) -> Result<(), Box<dyn Error>> {
   let shg = dx.load_geometry_shader(&res[8]);
   println!("shv: {:08x} shp: {:08x} shg: {:08x}",
     shv.handle(), shp.handle(), shg.handle());
+  dx.watch_vertex_shader(&res[6], &shv); // reload when recompiled
+  dx.watch_pixel_shader(&res[7], &shp);
 
   // DX11 constant buffer
   // cbv slot 4 cb_Test(f4 g_Test, f4[4] g_Arr)
@@ -117,15 +137,30 @@ pub fn screen(p: &str) -> Result<(), Box<dyn Error>> {
   let cbv = dx.create_constant_buffer(5, 4);
   let cbp = dx.create_constant_buffer(5, 4);
   let cbg = dx.create_constant_buffer(0, 0);
-  let cb5 = dx.create_constant_buffer(1, 5);
-  let cb6 = dx.create_constant_buffer(2, 6);
-  let cb7 = dx.create_constant_buffer(1, 7);
-  let cb8 = dx.create_constant_buffer(4, 8);
+  let mut cb5 = dx.create_constant_buffer(1, 5);
+  let mut cb6 = dx.create_constant_buffer(2, 6);
+  let mut cb7 = dx.create_constant_buffer(1, 7);
+  let mut cb8 = dx.create_typed_constant_buffer::<CamLight>(8);
   println!("cbv: {:08x}, cbp: {:08x} cbg: {:08x}",
     cbv.handle(), cbp.handle(), cbg.handle());
   println!("cb5: {:08x}, cb6: {:08x} cb7: {:08x} cb8: {:08x}",
     cb5.handle(), cb6.handle(), cb7.handle(), cb8.handle());
+  let hps = Hlsl::load(base.join("shader_PS.hlsl").to_str().expect("str"),
+    &[])?; // slots and sizes must match the shader source
+  for cb in [&cbp, &cb5, &cb6, &cb7] {
+    hps.cbuffer_at(cb.s).ok_or("no cbuffer")?.check(cb)?;
+  }
+  hps.cbuffer_at(cb8.s).ok_or("no cbuffer")?.check(&cb8)?;
+  hps.verify::<CamLight>("cb_CamLight")?;
+  let prog = ProgramDesc::new().vertex(&shv).pixel(&shp)
+    .constant(DX_SHADERTYPE_VERTEX, &cbv)
+    .constant(DX_SHADERTYPE_PIXEL, &cbp)
+    .constant(DX_SHADERTYPE_PIXEL, &cb5)
+    .constant(DX_SHADERTYPE_PIXEL, &cb6)
+    .constant(DX_SHADERTYPE_PIXEL, &cb7)
+    .constant(DX_SHADERTYPE_PIXEL, &cb8).build()?;
 
+  let m_pi = std::f32::consts::PI;
   let lights = vec![
     light::LightParamSub::new(DX_LIGHTTYPE_DIRECTIONAL, // default light
       COLOR_F::from_u32(col[7]), COLOR_F::from_u32(col[7]), amb.clone(),
@@ -133,39 +168,38 @@ pub fn screen(p: &str) -> Result<(), Box<dyn Error>> {
     light::LightParamSub::new(DX_LIGHTTYPE_DIRECTIONAL,
       COLOR_F::from_u32(col[2]), COLOR_F::from_u32(col[2]), amb.clone(),
       VECTOR::get(&[0.0, -512.0, 512.0]), VECTOR::new(0.0, 1.0, -1.0)),
-    light::LightParamSub::new(DX_LIGHTTYPE_DIRECTIONAL,
+    light::LightParamSub::new(DX_LIGHTTYPE_POINT,
       COLOR_F::from_u32(col[6]), COLOR_F::from_u32(col[6]), amb.clone(),
-      VECTOR::get(&[512.0, -512.0, 0.0]), VECTOR::new(-1.0, 1.0, 0.0)),
-    light::LightParamSub::new(DX_LIGHTTYPE_DIRECTIONAL,
+      VECTOR::get(&[512.0, -512.0, 0.0]), VECTOR::new(-1.0, 1.0, 0.0))
+      .range_atten(2000.0, 0.5, 0.0005, 0.0),
+    light::LightParamSub::new(DX_LIGHTTYPE_SPOT,
       COLOR_F::from_u32(col[5]), COLOR_F::from_u32(col[5]), amb.clone(),
-      VECTOR::get(&[0.0, 512.0, 0.0]), VECTOR::new(0.0, -1.0, 0.0))];
-  let ls = (1..lights.len()).into_iter().map(|k| { // starts from 1 (0 default)
-    // if lights[k].light_type == DX_LIGHTTYPE_DIRECTIONAL {} // TODO: skip
-    let lt = dx.create_dir_light(lights[k].direction.clone()); // change later
-    println!("light[{}]: {:08x}", k, lt.handle());
+      VECTOR::get(&[0.0, 512.0, 0.0]), VECTOR::new(0.0, -1.0, 0.0))
+      .range_atten(2000.0, 0.5, 0.0, 0.0).angle(m_pi / 2.0, m_pi / 4.0)];
+  let ls = (1..lights.len()).map(|k| { // starts from 1 (0 default)
+    let lt = dx.create_light(&lights[k].to_param())?;
+    println!("light[{}]: {:08x} {:?}", k, lt.handle(), lt.snapshot());
     lt.set_enable(TRUE);
-    lt.set_dif_color(lights[k].diffuse.clone());
-    lt.set_spc_color(lights[k].specular.clone());
-    lt.set_amb_color(lights[k].ambient.clone());
-    lt.set_position(lights[k].position.clone()); // not direction
-    lt.set_direction(lights[k].direction.clone()); // after construct
-    lt
-  }).collect::<Vec<_>>();
+    Ok(lt)
+  }).collect::<Result<Vec<_>, Box<dyn Error>>>()?;
   println!("lights: {} + 1", ls.len());
 
   init_font_to_handle();
   let fsys = dx.create_font("Arial\0", 32, 1, -1, -1, -1, TRUE); // italic
   let fdat = dx.load_font(&res[9]);
   println!("fsys: {:08x} fdat: {:08x}", fsys.handle(), fdat.handle());
-  let ani = dx.load_div_graph(&res[10], 12, 4, 3, 64, 64, FALSE, 0, 0);
+  let ani = dx.load_div_graph(&res[10], 12, 4, 3, 64, 64, FALSE, 0, 0)?;
   // for a in ani.iter() { println!("ani: {:08x}", a.handle()); }
-  let blk = dx.load_div_graph(&res[11], 8, 1, 8, 8, 8, FALSE, 8, 0);
+  let mut anm = Animation::new(&ani);
+  anm.add_clip("walk", Clip::range(0, ani.len(), 8.0, PlayMode::Loop)?);
+  anm.play("walk");
+  let blk = dx.load_div_graph(&res[11], 8, 1, 8, 8, 8, FALSE, 8, 0)?;
   // for b in blk.iter() { println!("blk: {:08x}", b.handle()); }
   let bls = dx.make_graphs_from_div_graph(&blk, TRUE, TRUE, FALSE); // shader
   let t6f = dx.load_graph(&res[12]); // shader 6 faces on the one texture
   let gds = dx.make_graph(64, 64, FALSE); // empty for clipping
-  let twh = dx.make_graph_color(64, 64, get_color(255, 255, 255),
-    TRUE, TRUE, FALSE); // white texture (through vertex color)
+  let twh = dx.make_graph_color(64, 64,
+    get_color(255, 255, 255))?; // white texture (through vertex color)
 
   select_midi_mode(DX_MIDIMODE_MCI);
   bgm.volume(96);
@@ -184,34 +218,46 @@ pub fn screen(p: &str) -> Result<(), Box<dyn Error>> {
   screen_flip();
   wait_key();
 
+  let mut rstk = RenderStack::new();
+  let rs3d = RenderState{
+    blend_mode: DX_BLENDMODE_NOBLEND, blend_param: 0, // not for shader
+    use_z_buffer: TRUE, write_z_buffer: TRUE,
+    back_culling: TRUE, // small true is not same as 1 or TRUE
+    lighting: TRUE, specular: TRUE, // default TRUE
+    global_ambient: COLOR_F::from_u32(col[5])};
+
+  let mut pxs = Batch2D::points();
+  for r in 0..360 {
+    for c in 0..480 {
+      let col = COLOR_U8::new(32, (192 - r / 2) as u8, (255 - c / 2) as u8,
+        255);
+      pxs.pixel(80 + c, 60 + r, &col);
+    }
+  }
+
   set_draw_screen(DX_SCREEN_BACK);
-  let m_pi = std::f32::consts::PI;
   let n = 360i32;
   let m = 4;
   for tick in 0..n * m {
     if process_message() != 0 { break; }
     if check_hit_key(0x01) != 0 { break; } // KEY_INPUT_ESCAPE
     if check_hit_key(0x10) != 0 { break; } // KEY_INPUT_Q
+    dx.reload_changed(); // .vso .pso
     clear_draw_screen(NULL);
-    set_use_z_buffer_3d(TRUE);
-    set_write_z_buffer_3d(TRUE);
+    rstk.apply(&rs3d); // only changes after the first frame
     // set_use_z_buffer_flag(TRUE);
     // set_write_z_buffer_flag(TRUE);
     // set_draw_z(0.2);
-    set_draw_blend_mode(DX_BLENDMODE_NOBLEND, 0); // not for shader
-    // loss time test draw many pixel
-    for r in 0..360 {
-      for c in 0..480 {
-        draw_pixel(80 + c, 60 + r, get_color(255 - c / 2, 192 - r / 2, 32));
-      }
-    }
+    // loss time test draw many pixel (in one call)
+    pxs.draw(FALSE);
     let i = tick * 640 / (n * m);
     let anim = (tick >> 6) as usize;
     [&grp, &tex][anim % 2].draw(i * 4 / 8, i * 3 / 8, TRUE); // transparent
     let anim = (tick >> 3) as usize % (2 * ani.len());
     let left = 80;
     let top = 420 - 64 - 13 * anim as i32;
-    ani[anim % ani.len()].draw(left, top, TRUE);
+    anm.draw(left, top, TRUE);
+    anm.update(1.0); // 1 tick
     gds.get_draw_screen(left, top, left + 64, top + 64, TRUE); // clipping
 
     let g = dx.make_graph(480, 360, FALSE); // must unreg inner allocation
@@ -224,9 +270,6 @@ pub fn screen(p: &str) -> Result<(), Box<dyn Error>> {
     dx.unreg(Box::new(g));
 
     set_draw_screen(DX_SCREEN_WORK);
-    set_use_lighting(TRUE); // default TRUE
-    set_use_specular(TRUE); // default TRUE
-    set_global_ambient_light(COLOR_F::from_u32(col[5]));
     set_use_light_angle_attenuation(TRUE); // default TRUE
     set_light_enable(TRUE); // default TRUE
     set_light_dif_color(lights[0].diffuse.clone());
@@ -237,23 +280,12 @@ pub fn screen(p: &str) -> Result<(), Box<dyn Error>> {
     // set_light_range_atten(1000.0, 1.0, 0.5, 0.25);
     // set_light_angle(2.0 * pi / 3.0, pi / 2.0);
     // set_light_use_shadow_map(ssi, TRUE);
-    set_use_back_culling(TRUE); // small true is not same as 1 or TRUE
     // tex.set_to_shader(0); // single texture
     // [&grp, &tex][anim % 2].set_to_shader(0); // changing texture
     // ani[anim % ani.len()].set_to_shader(0); // transparent (black on black)
     // gds.set_to_shader(0); // clipped rect of 2d screen
-    shv.set_shader();
-    shp.set_shader();
-    // shg.set_shader();
-
-    // for DX11 (may be constant address in the loop)
-    let b_cbv = cbv.as_slice_mut();
-    let b_cbp = cbp.as_slice_mut();
-    let b_cbg = cbg.as_slice_mut();
-    let b_cb5 = cb5.as_slice_mut();
-    let b_cb6 = cb6.as_slice_mut();
-    let b_cb7 = cb7.as_slice_mut();
-    let b_cb8 = cb8.as_slice_mut();
+    // shaders and constant buffers are set by prog.bind() below
+
     if tick == 0 {
       let nl = dx.get_enable_light_handle_num();
       for l in 0..nl {
@@ -261,19 +293,19 @@ pub fn screen(p: &str) -> Result<(), Box<dyn Error>> {
         println!("lh[{}] = {:08x}", l, lh);
       }
       // for DX11 (may be constant address in the loop)
-      println!("cbv buffer: {:?}", cbv.ptr_mut());
-      println!("cbp buffer: {:?}", cbp.ptr_mut());
-      println!("cbg buffer: {:?}", cbg.ptr_mut());
-      println!("cb5 buffer: {:?}", cb5.ptr_mut());
-      println!("cb6 buffer: {:?}", cb6.ptr_mut());
-      println!("cb7 buffer: {:?}", cb7.ptr_mut());
-      println!("cb8 buffer: {:?}", cb8.ptr_mut());
+      println!("cbv buffer: {:?}", cbv.ptr());
+      println!("cbp buffer: {:?}", cbp.ptr());
+      println!("cbg buffer: {:?}", cbg.ptr());
+      println!("cb5 buffer: {:?}", cb5.ptr());
+      println!("cb6 buffer: {:?}", cb6.ptr());
+      println!("cb7 buffer: {:?}", cb7.ptr());
+      println!("cb8 buffer: {:?}", cb8.ptr());
 /*
       // for DX9
-      proc_sh(&shv, &["g_Reg0\0", "g_Reg1\0", "g_Test\0", "g_Arr\0",
-        "g_Common\0", "g_Base\0", "g_OtherMatrix\0", "g_LocalWorldMatrix\0"]);
-      proc_sh(&shp, &["g_Reg0\0", "g_Reg1\0", "g_Test\0", "g_Arr\0",
-        "g_Common\0", "g_Base\0", "g_ShadowMap\0", "g_Filter", "g_CL\0"]);
+      proc_sh(&shv, &["g_Reg0", "g_Reg1", "g_Test", "g_Arr",
+        "g_Common", "g_Base", "g_OtherMatrix", "g_LocalWorldMatrix"]);
+      proc_sh(&shp, &["g_Reg0", "g_Reg1", "g_Test", "g_Arr",
+        "g_Common", "g_Base", "g_ShadowMap", "g_Filter", "g_CL"]);
 */
     }
 
@@ -304,40 +336,33 @@ pub fn screen(p: &str) -> Result<(), Box<dyn Error>> {
 
     // for DX11
     if tick <= 1 {
-      proc_cb("b_cbv", b_cbv);
-      proc_cb("b_cbp", b_cbp);
-      proc_cb("b_cbg", b_cbg);
-      proc_cb("b_cb5", b_cb5);
-      proc_cb("b_cb6", b_cb6);
-      proc_cb("b_cb7", b_cb7);
-      proc_cb("b_cb8", b_cb8);
+      proc_cb("b_cbv", cbv.view());
+      proc_cb("b_cbp", cbp.view());
+      proc_cb("b_cbg", cbg.view());
+      proc_cb("b_cb5", cb5.view());
+      proc_cb("b_cb6", cb6.view());
+      proc_cb("b_cb7", cb7.view());
+      proc_cb("b_cb8", cb8.view());
     }
-    cbv.update();
-    shv.set_const(&cbv);
-    cbp.update();
-    shp.set_const(&cbp);
-    cbg.update();
+    cbg.update(); // geometry shader is not used
     shg.set_const(&cbg);
-    b_cb5[0] = FLOAT4::new(0.0, 0.0, 0.0, 1.0); // cb_cam_pos4
-    cb5.update();
-    shp.set_const(&cb5);
-    b_cb6[0] = FLOAT4::new(0.8, 0.8, 0.8, 0.8); // cb_a
-    b_cb6[1] = FLOAT4::new(0.0, 0.0, 0.0, 0.0); // cb_b
-    cb6.update();
-    shp.set_const(&cb6);
-    b_cb7[0] = FLOAT4::new(0.0, 0.0, 0.0, 0.0); // cb_c
-    cb7.update();
-    shp.set_const(&cb7);
-    b_cb8[0] = FLOAT4::new(rc * c, rc * s, rs, 1.0); // g_CL.cam_pos4
-    b_cb8[1] = FLOAT4::new(0.0, 0.0, 0.0, 1.0); // g_CL.cam_lat4
-    b_cb8[2] = FLOAT4::new(0.8, 0.8, 0.8, 0.8); // g_CL.r[0] (light ratio)
-    b_cb8[3] = FLOAT4::new(0.0, 0.0, 0.0, 1.0); // g_CL.r[1] (w: camera angle)
-    cb8.update();
-    shp.set_const(&cb8);
+    cb5.map()[0] = FLOAT4::new(0.0, 0.0, 0.0, 1.0); // cb_cam_pos4
+    {
+      let mut b = cb6.map(); // updated on drop
+      b[0] = FLOAT4::new(0.8, 0.8, 0.8, 0.8); // cb_a
+      b[1] = FLOAT4::new(0.0, 0.0, 0.0, 0.0); // cb_b
+    }
+    cb7.map()[0] = FLOAT4::new(0.0, 0.0, 0.0, 0.0); // cb_c
+    *cb8.map().get_mut() = CamLight{
+      cam_pos4: FLOAT4::new(rc * c, rc * s, rs, 1.0),
+      cam_lat4: FLOAT4::new(0.0, 0.0, 0.0, 1.0),
+      r: [FLOAT4::new(0.8, 0.8, 0.8, 0.8), FLOAT4::new(0.0, 0.0, 0.0, 1.0)]};
+    prog.bind(); // set cbv cbp cb5 cb6 cb7 cb8 (update when written)
 /*
     // for DX9
-    set_ps_const_f(VecL0, COLOR_F::get(&[1.0, 1.0, 1.0, 1.0]).as_float4());
-    set_ps_const_f(PosL0, COLOR_F::get(&[0.0, 0.0, 0.0, 1.0]).as_float4());
+    let pc = ShaderConstants::new(&shp, &["g_Reg0", "g_Reg1"])?;
+    pc.set_f("g_Reg0", &COLOR_F::get(&[1.0, 1.0, 1.0, 1.0]).as_float4())?;
+    pc.set_f("g_Reg1", &COLOR_F::get(&[0.0, 0.0, 0.0, 1.0]).as_float4())?;
 */
 
     draw_line_3d(VECTOR::zeros(), VECTOR::new(-512.0, 0.0, 0.0), col[7]);
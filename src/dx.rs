@@ -154,6 +154,21 @@ impl FLOAT4 {
   pub fn get(v: &[f32; 4]) -> Self { FLOAT4::new(v[0], v[1], v[2], v[3]) }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+#[repr(C)]
+pub struct INT4 {
+  pub x: i32,
+  pub y: i32,
+  pub z: i32,
+  pub w: i32
+}
+
+impl INT4 {
+  pub fn new(x: i32, y: i32, z: i32, w: i32) -> Self { INT4{x, y, z, w} }
+  pub fn zeros() -> Self { INT4::new(0, 0, 0, 0) }
+  pub fn get(v: &[i32; 4]) -> Self { INT4::new(v[0], v[1], v[2], v[3]) }
+}
+
 #[derive(Debug, Clone)]
 #[repr(C)]
 pub struct DOUBLE4 {
@@ -169,6 +184,26 @@ impl DOUBLE4 {
   pub fn get(v: &[f64; 4]) -> Self { DOUBLE4::new(v[0], v[1], v[2], v[3]) }
 }
 
+#[derive(Debug, Clone)]
+#[repr(C)]
+pub struct VERTEX2D {
+  pub pos: VECTOR,
+  pub rhw: f32, // 1.0
+  pub dif: COLOR_U8,
+  pub uv: FLOAT2 // u: f32, v: f32
+}
+
+#[derive(Debug, Clone)]
+#[repr(C)]
+pub struct VERTEX2DSHADER {
+  pub pos: VECTOR,
+  pub rhw: f32, // 1.0
+  pub dif: COLOR_U8,
+  pub spc: COLOR_U8,
+  pub uv: FLOAT2, // u: f32, v: f32
+  pub suv: FLOAT2 // su: f32, sv: f32
+}
+
 #[derive(Debug, Clone)]
 #[repr(C)]
 pub struct VERTEX3D {
@@ -285,11 +320,20 @@ extern "stdcall" {
 
   pub fn ClearDrawScreen(rct: *const c_void) -> i32;
   pub fn SetDrawScreen(ds: i32) -> i32; // DX_SCREEN_FRONT DX_SCREEN_BACK
+  pub fn GetDrawScreen() -> i32;
+  pub fn GetDrawScreenSize(xsz: *mut i32, ysz: *mut i32) -> i32;
+  pub fn SaveDrawScreenToBMP(l: i32, t: i32, r: i32, b: i32,
+    fname: *const u8) -> i32;
+  pub fn SaveDrawScreenToPNG(l: i32, t: i32, r: i32, b: i32,
+    fname: *const u8, compression_level: i32) -> i32; // -1
+  pub fn SaveDrawScreenToJPEG(l: i32, t: i32, r: i32, b: i32,
+    fname: *const u8, quality: i32, sample2x1: i32) -> i32; // 80 TRUE
   pub fn ScreenFlip() -> i32;
 
   pub fn SelectMidiMode(md: i32) -> i32;
   pub fn InitMusicMem() -> i32;
   pub fn LoadMusicMem(mid: *const u8) -> i32;
+  pub fn LoadMusicMemByMemImage(image: *const c_void, sz: usize) -> i32;
   pub fn DeleteMusicMem(mh: i32) -> i32;
   pub fn PlayMusicMem(mh: i32, playtype: i32) -> i32;
   pub fn StopMusicMem(mh: i32) -> i32;
@@ -297,12 +341,30 @@ extern "stdcall" {
   pub fn SetVolumeMusicMem(volume: i32, mh: i32) -> i32;
 
   pub fn LoadSoundMem(snd: *const u8) -> i32;
+  pub fn LoadSoundMemByMemImage(image: *const c_void, sz: usize,
+    bufnum: i32, union_handle: i32) -> i32; // bufnum=3 union_handle=-1
   pub fn DeleteSoundMem(sh: i32, logout: i32) -> i32;
   pub fn PlaySoundMem(sh: i32, playtype: i32, topposition: i32) -> i32;
   pub fn StopSoundMem(sh: i32) -> i32;
   pub fn ChangeVolumeSoundMem(volumepal: i32, sh: i32) -> i32;
 
+  pub fn OpenMovieToGraph(fname: *const u8, full_color: i32) -> i32; // TRUE
+  pub fn PlayMovieToGraph(gh: i32, playtype: i32, sys_play: i32) -> i32;
+  pub fn PauseMovieToGraph(gh: i32, sys_pause: i32) -> i32;
+  pub fn SeekMovieToGraph(gh: i32, ms: i32) -> i32;
+  pub fn TellMovieToGraph(gh: i32) -> i32; // ms
+  pub fn GetMovieStateToGraph(gh: i32) -> i32; // 1: playing 0: stopped
+  pub fn GetMovieTotalFrameToGraph(gh: i32) -> i32;
+  pub fn SetPlaySpeedRateMovieToGraph(gh: i32, rate: f64) -> i32;
+  pub fn ChangeMovieVolumeToGraph(volume: i32, gh: i32) -> i32; // 0-255
+
   pub fn MakeScreen(xsz: i32, ysz: i32, trans: i32) -> i32;
+  pub fn SetCreateDrawValidGraphChannelNum(n: i32) -> i32; // 0: default
+  pub fn GetCreateDrawValidGraphChannelNum() -> i32;
+  pub fn SetDrawValidFloatTypeGraphCreateFlag(flg: i32) -> i32;
+  pub fn GetDrawValidFloatTypeGraphCreateFlag() -> i32;
+  pub fn SetCreateGraphChannelBitDepth(bits: i32) -> i32; // 0: default
+  pub fn GetCreateGraphChannelBitDepth() -> i32;
   pub fn GetGraphSize(gh: i32, xsz: *mut i32, ysz: *mut i32) -> i32;
   pub fn GetDrawScreenGraph(l: i32, t: i32, r: i32, b: i32,
     gh: i32, use_client_flag: i32) -> i32;
@@ -311,6 +373,7 @@ extern "stdcall" {
     xnum: i32, ynum: i32, xsz: i32, ysz: i32, handle_buf: *mut i32,
     not_use_3d_flag: i32, xstride: i32, ystride: i32) -> i32;
   pub fn LoadGraph(fname: *const u8) -> i32;
+  pub fn ReloadGraph(fname: *const u8, gh: i32, reverse: i32) -> i32;
   pub fn DeleteGraph(gh: i32, logout: i32) -> i32;
   pub fn DrawGraph(x: i32, y: i32, gh: i32, trans: i32) -> i32;
   pub fn DrawTurnGraph(x: i32, y: i32, gh: i32, trans: i32) -> i32;
@@ -324,11 +387,100 @@ extern "stdcall" {
     gh: i32, trans: i32, reversex: i32, reversey: i32) -> i32;
   pub fn DrawRectExtendGraph(l: i32, t: i32, r: i32, b: i32,
     srcx: i32, srcy: i32, w: i32, h: i32, gh: i32, trans: i32) -> i32;
+  pub fn DrawRotaGraphF(xf: f32, yf: f32, extrate: f64, angle: f64,
+    gh: i32, trans: i32, reversex: i32, reversey: i32) -> i32;
+  pub fn DrawGraphF(xf: f32, yf: f32, gh: i32, trans: i32) -> i32;
+  pub fn DrawRectRotaGraph(x: i32, y: i32, srcx: i32, srcy: i32,
+    w: i32, h: i32, extrate: f64, angle: f64,
+    gh: i32, trans: i32, reversex: i32, reversey: i32) -> i32;
+  pub fn DrawRectRotaGraphF(xf: f32, yf: f32, srcx: i32, srcy: i32,
+    w: i32, h: i32, extrate: f64, angle: f64,
+    gh: i32, trans: i32, reversex: i32, reversey: i32) -> i32;
+  pub fn DrawRotaGraph3(x: i32, y: i32, cx: i32, cy: i32,
+    extratex: f64, extratey: f64, angle: f64,
+    gh: i32, trans: i32, reversex: i32, reversey: i32) -> i32;
+  pub fn DrawRotaGraph3F(xf: f32, yf: f32, cxf: f32, cyf: f32,
+    extratex: f64, extratey: f64, angle: f64,
+    gh: i32, trans: i32, reversex: i32, reversey: i32) -> i32;
+  pub fn DrawRectRotaGraph3F(xf: f32, yf: f32, srcx: i32, srcy: i32,
+    w: i32, h: i32, cxf: f32, cyf: f32,
+    extratex: f64, extratey: f64, angle: f64,
+    gh: i32, trans: i32, reversex: i32, reversey: i32) -> i32;
+  pub fn DerivationGraph(srcx: i32, srcy: i32, w: i32, h: i32,
+    src_gh: i32) -> i32;
+
+  pub fn InitSoftImage() -> i32;
+  pub fn LoadSoftImage(fname: *const u8) -> i32;
+  pub fn LoadARGB8ColorSoftImage(fname: *const u8) -> i32;
+  pub fn MakeARGB8ColorSoftImage(xsz: i32, ysz: i32) -> i32;
+  pub fn DeleteSoftImage(sih: i32) -> i32;
+  pub fn GetSoftImageSize(sih: i32, w: *mut i32, h: *mut i32) -> i32;
+  pub fn GetImageAddressSoftImage(sih: i32) -> *mut c_void;
+  pub fn GetPitchSoftImage(sih: i32) -> i32;
+  pub fn GetPixelSoftImage(sih: i32, x: i32, y: i32,
+    r: *mut i32, g: *mut i32, b: *mut i32, a: *mut i32) -> i32;
+  pub fn DrawPixelSoftImage(sih: i32, x: i32, y: i32,
+    r: i32, g: i32, b: i32, a: i32) -> i32;
+  pub fn FillSoftImage(sih: i32, r: i32, g: i32, b: i32, a: i32) -> i32;
+  pub fn ClearRectSoftImage(sih: i32, x: i32, y: i32, w: i32, h: i32) -> i32;
+  pub fn BltSoftImage(srcx: i32, srcy: i32, w: i32, h: i32, src_sih: i32,
+    dstx: i32, dsty: i32, dst_sih: i32) -> i32;
+  pub fn ReverseSoftImageH(sih: i32) -> i32;
+  pub fn ReverseSoftImageV(sih: i32) -> i32;
+  pub fn ReverseSoftImage(sih: i32) -> i32;
+  pub fn SaveSoftImageToPng(fname: *const u8, sih: i32,
+    compression_level: i32) -> i32; // 0-9 or -1
+  pub fn SaveSoftImageToBmp(fname: *const u8, sih: i32) -> i32;
+  pub fn SaveSoftImageToJpeg(fname: *const u8, sih: i32,
+    quality: i32, sample2x1: i32) -> i32; // quality 0-100
+  pub fn GetDrawScreenSoftImage(l: i32, t: i32, r: i32, b: i32,
+    sih: i32) -> i32;
+  pub fn CreateGraphFromSoftImage(sih: i32) -> i32;
+  pub fn ReCreateGraphFromSoftImage(sih: i32, gh: i32) -> i32;
+  pub fn CreateGraphFromMem(rgb_file_image: *const c_void, rgb_sz: i32,
+    alpha_file_image: *const c_void, alpha_sz: i32,
+    texture_flag: i32, reverse_flag: i32) -> i32; // NULL 0 TRUE FALSE
+
+  pub fn InitMask() -> i32;
+  pub fn CreateMaskScreen() -> i32;
+  pub fn DeleteMaskScreen() -> i32;
+  pub fn FillMaskScreen(flg: i32) -> i32; // 0: transparent 1: masked
+  pub fn SetUseMaskScreenFlag(flg: i32) -> i32;
+  pub fn GetUseMaskScreenFlag() -> i32;
+  pub fn SetMaskReverseEffectFlag(flg: i32) -> i32;
+  pub fn SetMaskScreenGraph(gh: i32) -> i32; // -1: release
+  pub fn SetMaskScreenGraphUseChannel(ch: i32) -> i32; // DX_MASKGRAPH_CH_A
+  pub fn MakeMask(xsz: i32, ysz: i32) -> i32;
+  pub fn LoadMask(fname: *const u8) -> i32;
+  pub fn DeleteMask(mh: i32) -> i32;
+  pub fn GetMaskSize(w: *mut i32, h: *mut i32, mh: i32) -> i32;
+  pub fn SetDataToMask(w: i32, h: i32, data: *const c_void, mh: i32) -> i32;
+  pub fn DrawMask(x: i32, y: i32, mh: i32, trans_mode: i32) -> i32;
+  pub fn DrawFillMask(l: i32, t: i32, r: i32, b: i32, mh: i32) -> i32;
+
+  // GraphFilter etc are variadic (C# DLL exports S version fixed 10 params)
+  pub fn GraphFilterS(gh: i32, filter_type: i32,
+    p0: i32, p1: i32, p2: i32, p3: i32, p4: i32,
+    p5: i32, p6: i32, p7: i32, p8: i32, p9: i32) -> i32;
+  pub fn GraphFilterBltS(src_gh: i32, dst_gh: i32, filter_type: i32,
+    p0: i32, p1: i32, p2: i32, p3: i32, p4: i32,
+    p5: i32, p6: i32, p7: i32, p8: i32, p9: i32) -> i32;
+  pub fn GraphBlendS(gh: i32, blend_gh: i32, blend_ratio: i32,
+    blend_type: i32,
+    p0: i32, p1: i32, p2: i32, p3: i32, p4: i32,
+    p5: i32, p6: i32, p7: i32, p8: i32, p9: i32) -> i32;
+  pub fn GraphBlendBltS(src_gh: i32, blend_gh: i32, dst_gh: i32,
+    blend_ratio: i32, blend_type: i32,
+    p0: i32, p1: i32, p2: i32, p3: i32, p4: i32,
+    p5: i32, p6: i32, p7: i32, p8: i32, p9: i32) -> i32;
 
   pub fn InitShader() -> i32;
   pub fn LoadVertexShader(vso: *const u8) -> i32;
   pub fn LoadPixelShader(pso: *const u8) -> i32;
   pub fn LoadGeometryShader(gso: *const u8) -> i32;
+  pub fn LoadVertexShaderFromMem(image: *const c_void, sz: i32) -> i32;
+  pub fn LoadPixelShaderFromMem(image: *const c_void, sz: i32) -> i32;
+  pub fn LoadGeometryShaderFromMem(image: *const c_void, sz: i32) -> i32;
   pub fn DeleteShader(h: i32) -> i32;
 
   pub fn CreateDirLightHandle(d: VECTOR) -> i32;
@@ -402,6 +554,22 @@ extern "stdcall" {
     n: *const u8, l: usize, sh: i32) -> i32; // DX9
   pub fn SetVSConstF(i: i32, p: FLOAT4) -> i32; // DX9
   pub fn SetPSConstF(i: i32, p: FLOAT4) -> i32; // DX9
+  pub fn SetVSConstFMtx(i: i32, p: MATRIX) -> i32; // DX9
+  pub fn SetPSConstFMtx(i: i32, p: MATRIX) -> i32; // DX9
+  pub fn SetVSConstFMtxT(i: i32, p: MATRIX) -> i32; // DX9 transpose
+  pub fn SetPSConstFMtxT(i: i32, p: MATRIX) -> i32; // DX9 transpose
+  pub fn SetVSConstI(i: i32, p: INT4) -> i32; // DX9
+  pub fn SetPSConstI(i: i32, p: INT4) -> i32; // DX9
+  pub fn SetVSConstB(i: i32, p: i32) -> i32; // DX9
+  pub fn SetPSConstB(i: i32, p: i32) -> i32; // DX9
+  pub fn SetVSConstFArray(i: i32, p: *const FLOAT4, n: i32) -> i32; // DX9
+  pub fn SetPSConstFArray(i: i32, p: *const FLOAT4, n: i32) -> i32; // DX9
+  pub fn SetVSConstIArray(i: i32, p: *const INT4, n: i32) -> i32; // DX9
+  pub fn SetPSConstIArray(i: i32, p: *const INT4, n: i32) -> i32; // DX9
+  pub fn SetVSConstBArray(i: i32, p: *const i32, n: i32) -> i32; // DX9
+  pub fn SetPSConstBArray(i: i32, p: *const i32, n: i32) -> i32; // DX9
+  pub fn ResetVSConstF(i: i32, n: i32) -> i32; // DX9
+  pub fn ResetPSConstF(i: i32, n: i32) -> i32; // DX9
 
   pub fn SetUseBackCulling(flg: i32) -> i32;
   pub fn SetRenderTargetToShader(target_index: i32, draw_screen: i32,
@@ -430,6 +598,18 @@ extern "stdcall" {
   pub fn CreateViewportMatrix(m: *mut MATRIX,
     cx: f32, cy: f32, w: f32, h: f32) -> i32;
   pub fn SetTransformToViewport(m: *const MATRIX) -> i32;
+  pub fn GetTransformToViewMatrix(m: *mut MATRIX) -> i32;
+  pub fn SetTransformToView(m: *const MATRIX) -> i32;
+  pub fn GetTransformToViewportMatrix(m: *mut MATRIX) -> i32;
+
+  pub fn DrawPolygon2D(va: *const VERTEX2D, npolygons: i32,
+    gh: i32, trans: i32) -> i32; // gh=DX_NONE_GRAPH: vertex color only
+  pub fn DrawPrimitive2D(va: *const VERTEX2D, nvertices: i32, prim: i32,
+    gh: i32, trans: i32) -> i32; // prim=DX_PRIMTYPE_*
+  pub fn DrawPolygon2DToShader(va: *const VERTEX2DSHADER,
+    npolygons: i32) -> i32;
+  pub fn DrawPrimitive2DToShader(va: *const VERTEX2DSHADER, nvertices: i32,
+    prim: i32) -> i32;
 
   pub fn DrawPolygon3DToShader(va: *const VERTEX3DSHADER, npolygons: i32) -> i32;
   pub fn DrawPolygon3D(va: *const VERTEX3D, npolygons: i32,
@@ -440,6 +620,8 @@ extern "stdcall" {
   pub fn CreateFontToHandle(font: *const u8, sz: i32, thick: i32,
     fonttype: i32, charset: i32, edgesz: i32, italic: i32, handle: i32) -> i32;
   pub fn LoadFontDataToHandle(fname: *const u8, edgesz: i32) -> i32;
+  pub fn LoadFontDataFromMemToHandle(image: *const c_void, sz: i32,
+    edgesz: i32) -> i32;
   pub fn DrawStringToHandle(x: i32, y: i32, s: *const u8,
     color: u32, fh: i32, edgecolor: u32, vertical_flag: i32) -> i32;
   pub fn DrawFormatStringToHandle(x: i32, y: i32, color: u32, fh: i32,
@@ -449,6 +631,35 @@ extern "stdcall" {
   pub fn DrawPixel(x: i32, y: i32, c: u32) -> i32;
   pub fn DrawBox(l: i32, t: i32, r: i32, b: i32, c: u32, fill: i32) -> i32;
 
+  pub fn DrawLine(x1: i32, y1: i32, x2: i32, y2: i32,
+    c: u32, thickness: i32) -> i32; // thickness=1
+  pub fn DrawLineAA(x1: f32, y1: f32, x2: f32, y2: f32,
+    c: u32, thickness: f32) -> i32; // thickness=1.0
+  pub fn DrawLineBox(l: i32, t: i32, r: i32, b: i32, c: u32) -> i32;
+  pub fn DrawBoxAA(l: f32, t: f32, r: f32, b: f32,
+    c: u32, fill: i32, thickness: f32) -> i32;
+  pub fn DrawCircle(x: i32, y: i32, r: i32,
+    c: u32, fill: i32, thickness: i32) -> i32;
+  pub fn DrawCircleAA(x: f32, y: f32, r: f32, posnum: i32,
+    c: u32, fill: i32, thickness: f32) -> i32;
+  pub fn DrawOval(x: i32, y: i32, rx: i32, ry: i32,
+    c: u32, fill: i32, thickness: i32) -> i32;
+  pub fn DrawOvalAA(x: f32, y: f32, rx: f32, ry: f32, posnum: i32,
+    c: u32, fill: i32, thickness: f32) -> i32;
+  pub fn DrawTriangle(x1: i32, y1: i32, x2: i32, y2: i32, x3: i32, y3: i32,
+    c: u32, fill: i32) -> i32;
+  pub fn DrawTriangleAA(x1: f32, y1: f32, x2: f32, y2: f32, x3: f32, y3: f32,
+    c: u32, fill: i32, thickness: f32) -> i32;
+  pub fn DrawQuadrangle(x1: i32, y1: i32, x2: i32, y2: i32,
+    x3: i32, y3: i32, x4: i32, y4: i32, c: u32, fill: i32) -> i32;
+  pub fn DrawQuadrangleAA(x1: f32, y1: f32, x2: f32, y2: f32,
+    x3: f32, y3: f32, x4: f32, y4: f32,
+    c: u32, fill: i32, thickness: f32) -> i32;
+  pub fn DrawRoundRect(l: i32, t: i32, r: i32, b: i32, rx: i32, ry: i32,
+    c: u32, fill: i32) -> i32;
+  pub fn DrawRoundRectAA(l: f32, t: f32, r: f32, b: f32, rx: f32, ry: f32,
+    posnum: i32, c: u32, fill: i32, thickness: f32) -> i32;
+
   pub fn DrawPixel3D(p: VECTOR, c: u32) -> i32;
   pub fn DrawLine3D(s: VECTOR, e: VECTOR, c: u32) -> i32;
   pub fn DrawTriangle3D(p0: VECTOR, p1: VECTOR, p2: VECTOR,
@@ -478,6 +689,15 @@ pub const DX_SCREEN_BACK: i32 = -2;
 pub const DX_SCREEN_WORK: i32 = -3;
 pub const DX_SCREEN_FRONT: i32 = -4;
 
+pub const DX_NONE_GRAPH: i32 = -5;
+
+pub const DX_PRIMTYPE_POINTLIST: i32 = 1;
+pub const DX_PRIMTYPE_LINELIST: i32 = 2;
+pub const DX_PRIMTYPE_LINESTRIP: i32 = 3;
+pub const DX_PRIMTYPE_TRIANGLELIST: i32 = 4;
+pub const DX_PRIMTYPE_TRIANGLESTRIP: i32 = 5;
+pub const DX_PRIMTYPE_TRIANGLEFAN: i32 = 6;
+
 pub const DX_BLENDMODE_NOBLEND: i32 = 0;
 pub const DX_BLENDMODE_ALPHA: i32 = 1;
 pub const DX_BLENDMODE_ADD: i32 = 2;
@@ -497,6 +717,67 @@ pub const DX_BLENDMODE_CUSTOM: i32 = 32;
 /// more blend modes
 pub const DX_BLENDMODE_NUM: i32 = 33;
 
+pub const DX_MASKTRANS_WHITE: i32 = 0;
+pub const DX_MASKTRANS_BLACK: i32 = 1;
+pub const DX_MASKTRANS_NONE: i32 = 2;
+
+pub const DX_MASKGRAPH_CH_A: i32 = 0;
+pub const DX_MASKGRAPH_CH_R: i32 = 1;
+pub const DX_MASKGRAPH_CH_G: i32 = 2;
+pub const DX_MASKGRAPH_CH_B: i32 = 3;
+
+pub const DX_GRAPH_FILTER_MONO: i32 = 0;
+pub const DX_GRAPH_FILTER_GAUSS: i32 = 1;
+pub const DX_GRAPH_FILTER_DOWN_SCALE: i32 = 2;
+pub const DX_GRAPH_FILTER_BRIGHT_CLIP: i32 = 3;
+pub const DX_GRAPH_FILTER_BRIGHT_SCALE: i32 = 4;
+pub const DX_GRAPH_FILTER_HSB: i32 = 5;
+pub const DX_GRAPH_FILTER_INVERT: i32 = 6;
+pub const DX_GRAPH_FILTER_LEVEL: i32 = 7;
+pub const DX_GRAPH_FILTER_TWO_COLOR: i32 = 8;
+pub const DX_GRAPH_FILTER_GRADIENT_MAP: i32 = 9;
+pub const DX_GRAPH_FILTER_REPLACEMENT: i32 = 10;
+pub const DX_GRAPH_FILTER_PREMUL_ALPHA: i32 = 11;
+pub const DX_GRAPH_FILTER_INTERP_ALPHA: i32 = 12;
+pub const DX_GRAPH_FILTER_BICUBIC_SCALE: i32 = 17;
+pub const DX_GRAPH_FILTER_LANCZOS3_SCALE: i32 = 18;
+
+pub const DX_GRAPH_BLEND_NORMAL: i32 = 0;
+pub const DX_GRAPH_BLEND_RGBA_SELECT_MIX: i32 = 1;
+pub const DX_GRAPH_BLEND_MULTIPLE: i32 = 2;
+pub const DX_GRAPH_BLEND_DIFFERENCE: i32 = 3;
+pub const DX_GRAPH_BLEND_ADD: i32 = 4;
+pub const DX_GRAPH_BLEND_SCREEN: i32 = 5;
+pub const DX_GRAPH_BLEND_OVERLAY: i32 = 6;
+pub const DX_GRAPH_BLEND_DODGE: i32 = 7;
+pub const DX_GRAPH_BLEND_BURN: i32 = 8;
+pub const DX_GRAPH_BLEND_DARKEN: i32 = 9;
+pub const DX_GRAPH_BLEND_LIGHTEN: i32 = 10;
+pub const DX_GRAPH_BLEND_SOFTLIGHT: i32 = 11;
+pub const DX_GRAPH_BLEND_HARDLIGHT: i32 = 12;
+pub const DX_GRAPH_BLEND_EXCLUSION: i32 = 13;
+pub const DX_GRAPH_BLEND_NORMAL_ALPHACH: i32 = 14;
+pub const DX_GRAPH_BLEND_ADD_ALPHACH: i32 = 15;
+pub const DX_GRAPH_BLEND_MULTIPLE_A_ONLY: i32 = 16;
+
+pub const DX_RGBA_SELECT_SRC_R: i32 = 0;
+pub const DX_RGBA_SELECT_SRC_G: i32 = 1;
+pub const DX_RGBA_SELECT_SRC_B: i32 = 2;
+pub const DX_RGBA_SELECT_SRC_A: i32 = 3;
+pub const DX_RGBA_SELECT_BLEND_R: i32 = 4;
+pub const DX_RGBA_SELECT_BLEND_G: i32 = 5;
+pub const DX_RGBA_SELECT_BLEND_B: i32 = 6;
+pub const DX_RGBA_SELECT_BLEND_A: i32 = 7;
+
+pub const DX_CMP_NEVER: i32 = 1;
+pub const DX_CMP_LESS: i32 = 2;
+pub const DX_CMP_EQUAL: i32 = 3;
+pub const DX_CMP_LESSEQUAL: i32 = 4;
+pub const DX_CMP_GREATER: i32 = 5;
+pub const DX_CMP_NOTEQUAL: i32 = 6;
+pub const DX_CMP_GREATEREQUAL: i32 = 7;
+pub const DX_CMP_ALWAYS: i32 = 8;
+
 pub const DX_MIDIMODE_MCI: i32 = 0;
 
 pub const DX_PLAYTYPE_LOOPBIT: i32 = 2;
@@ -5,10 +5,31 @@ use crate::dx::*;
 
 pub mod music;
 pub mod sound;
+pub mod movie;
 pub mod graph;
+pub mod canvas;
+pub mod batch;
+pub mod slice;
+pub mod transform;
+pub mod soft;
+pub mod image;
+pub mod filter;
+pub mod post;
+pub mod mrt;
+pub mod cbuffer;
+pub mod hlsl;
 pub mod shader;
+pub mod program;
+pub mod constants;
+pub mod reload;
 pub mod light;
 pub mod font;
+pub mod render;
+pub mod json;
+pub mod xml;
+pub mod atlas;
+pub mod anim;
+pub mod tilemap;
 pub mod tdx;
 
 /// UV
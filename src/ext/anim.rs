@@ -0,0 +1,213 @@
+//! anim ext dx bridge for DxLib
+//!
+
+use std::error::Error;
+use std::collections::HashMap;
+
+use crate::ext::tdx::*;
+use crate::ext::graph::Graph;
+
+/// PlayMode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlayMode {
+  /// 0 1 2 0 1 2 ...
+  Loop,
+  /// 0 1 2 (stop at the last frame)
+  Once,
+  /// 0 1 2 1 0 1 ...
+  PingPong
+}
+
+/// Clip (sequence of frames)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clip {
+  /// index of graphs
+  pub frames: Vec<usize>,
+  /// duration of each frame (same unit as dt)
+  pub durations: Vec<f32>,
+  /// mode
+  pub mode: PlayMode,
+  /// (position in frames, name) fired when the frame is entered
+  pub events: Vec<(usize, String)>
+}
+
+/// Clip
+impl Clip {
+  /// constructor (the same duration for all frames)
+  pub fn new(frames: Vec<usize>, duration: f32, mode: PlayMode)
+    -> Result<Self, Box<dyn Error>> {
+    let durations = vec![duration; frames.len()];
+    Clip::with_durations(frames, durations, mode)
+  }
+  /// constructor (duration each frame, finite and greater than 0)
+  pub fn with_durations(frames: Vec<usize>, durations: Vec<f32>,
+    mode: PlayMode) -> Result<Self, Box<dyn Error>> {
+    if frames.is_empty() { return Err("clip: no frames".into()) }
+    if frames.len() != durations.len() {
+      return Err(format!("clip: {} frames and {} durations",
+        frames.len(), durations.len()).into())
+    }
+    if let Some(d) = durations.iter().find(|d| !(d.is_finite() && **d > 0.0)) {
+      return Err(format!("clip: duration {}", d).into())
+    }
+    Ok(Clip{frames, durations, mode, events: vec![]})
+  }
+  /// frames first..first+n
+  pub fn range(first: usize, n: usize, duration: f32, mode: PlayMode)
+    -> Result<Self, Box<dyn Error>> {
+    Clip::new((first..first + n).collect(), duration, mode)
+  }
+  /// add event
+  pub fn event(mut self, pos: usize, name: &str) -> Self {
+    self.events.push((pos, name.to_string()));
+    self
+  }
+  /// total duration of one pass
+  pub fn length(&self) -> f32 { self.durations.iter().sum() }
+  /// time to come back to the same state (None: Once)
+  pub fn period(&self) -> Option<f32> {
+    let d = &self.durations;
+    match self.mode {
+    PlayMode::Once => None,
+    PlayMode::Loop => Some(self.length()),
+    PlayMode::PingPong => Some(if d.len() < 2 { self.length() }
+      else { 2.0 * self.length() - d[0] - d[d.len() - 1] })
+    }
+  }
+}
+
+/// Player (state of a clip, independent of DxLib)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Player {
+  /// position in clip.frames
+  pub pos: usize,
+  /// elapsed time in the current frame
+  pub t: f32,
+  /// 1: forward, -1: backward (PingPong)
+  pub dir: i32,
+  /// playback speed (0: pause)
+  pub speed: f32,
+  /// Once reached the end
+  pub finished: bool,
+  /// events of the first frame are not fired yet
+  pub pending: bool
+}
+
+/// Default for Player
+impl Default for Player {
+  /// default
+  fn default() -> Self {
+    Player{pos: 0, t: 0.0, dir: 1, speed: 1.0, finished: false, pending: true}
+  }
+}
+
+/// Player
+impl Player {
+  /// constructor
+  pub fn new() -> Self { Player::default() }
+  /// rewind (keep speed)
+  pub fn reset(&mut self) {
+    *self = Player{speed: self.speed, ..Player::default()};
+  }
+  /// index of graphs
+  pub fn frame(&self, clip: &Clip) -> usize { clip.frames[self.pos] }
+  /// next position (None: finished)
+  fn next(&mut self, clip: &Clip) -> Option<usize> {
+    let n = clip.frames.len() as i32;
+    let p = self.pos as i32;
+    match clip.mode {
+    PlayMode::Loop => Some(((p + 1) % n) as usize),
+    PlayMode::Once => if p + 1 < n { Some(p as usize + 1) } else { None },
+    PlayMode::PingPong => {
+      if n == 1 { return Some(0) }
+      if p + self.dir < 0 || p + self.dir >= n { self.dir = -self.dir; }
+      Some((p + self.dir) as usize)
+    }
+    }
+  }
+  /// advance by dt (returns names of fired events, dt not finite is ignored)
+  /// - whole periods of Loop PingPong but the last are skipped
+  pub fn update(&mut self, clip: &Clip, dt: f32) -> Vec<String> {
+    let mut ev = vec![];
+    if clip.frames.is_empty() || !dt.is_finite() { return ev }
+    if self.pos >= clip.frames.len() { self.reset(); } // other clip
+    let fire = |ev: &mut Vec<String>, pos: usize|
+      ev.extend(clip.events.iter().filter(|(p, _)| *p == pos)
+        .map(|(_, n)| n.clone()));
+    if self.pending { self.pending = false; fire(&mut ev, self.pos); }
+    if self.finished { return ev }
+    self.t += dt * self.speed.max(0.0);
+    if let Some(l) = clip.period().filter(|l| *l > 0.0 && self.t >= *l) {
+      self.t = self.t % l + l; // events of the last period are fired
+    }
+    while self.t >= clip.durations[self.pos] {
+      match self.next(clip) {
+      None => { self.finished = true; self.t = 0.0; break },
+      Some(p) => {
+        self.t -= clip.durations[self.pos];
+        self.pos = p;
+        fire(&mut ev, p);
+      }
+      }
+    }
+    ev
+  }
+}
+
+/// Animation (graphs and clips)
+pub struct Animation {
+  /// graphs (not disposed by Animation)
+  pub graphs: Vec<Graph>,
+  /// clips by name
+  pub clips: HashMap<String, Clip>,
+  /// current clip name
+  pub cur: Option<String>,
+  /// state
+  pub player: Player
+}
+
+/// Animation
+impl Animation {
+  /// constructor (from load_div_graph etc)
+  pub fn new(vg: &[Graph]) -> Self {
+    Animation{graphs: vg.iter().map(|g| g.as_graph()).collect(),
+      clips: HashMap::new(), cur: None, player: Player::new()}
+  }
+  /// add clip (the current clip replaced is played from the beginning)
+  pub fn add_clip(&mut self, n: &str, clip: Clip) -> &mut Self {
+    if self.cur.as_deref() == Some(n) { self.player.reset(); }
+    self.clips.insert(n.to_string(), clip);
+    self
+  }
+  /// current clip
+  pub fn clip(&self) -> Option<&Clip> {
+    self.cur.as_ref().and_then(|n| self.clips.get(n))
+  }
+  /// play clip from the beginning (false when not found)
+  pub fn play(&mut self, n: &str) -> bool {
+    if !self.clips.contains_key(n) { return false }
+    self.cur = Some(n.to_string());
+    self.player.reset();
+    true
+  }
+  /// playback speed (0: pause)
+  pub fn set_speed(&mut self, s: f32) { self.player.speed = s; }
+  /// Once reached the end
+  pub fn finished(&self) -> bool { self.player.finished }
+  /// advance by dt (returns names of fired events)
+  pub fn update(&mut self, dt: f32) -> Vec<String> {
+    match self.cur.as_ref().and_then(|n| self.clips.get(n)) {
+    None => vec![],
+    Some(c) => self.player.update(c, dt)
+    }
+  }
+  /// current graph
+  pub fn graph(&self) -> Option<&Graph> {
+    self.clip().and_then(|c| c.frames.get(self.player.pos))
+      .and_then(|f| self.graphs.get(*f))
+  }
+  /// draw current graph
+  pub fn draw(&self, x: i32, y: i32, trans: i32) {
+    if let Some(g) = self.graph() { g.draw(x, y, trans); }
+  }
+}
@@ -0,0 +1,339 @@
+//! atlas ext dx bridge for DxLib
+//!
+//! descriptor formats
+//!
+//! - TexturePacker JSON (Hash) `{"frames": {"name": {...}}, "meta": {...}}`
+//! - TexturePacker JSON (Array) `{"frames": [{"filename": "name", ...}]}`
+//! - simple TOML grid (`.toml`)
+//!
+//! nine slice insets (left top right bottom) are optional
+//! `"insets": [8, 8, 8, 8]` in each JSON frame
+//!
+//! ```toml
+//! image = "_img_256x256_16x64x64.png"
+//! [grid]
+//! cell_w = 64
+//! cell_h = 64
+//! columns = 4
+//! rows = 3
+//! count = 12 # default columns * rows
+//! margin = 0 # default 0
+//! spacing = 0 # default 0
+//! prefix = "walk_" # default "frame_" (names walk_0 walk_1 ...)
+//! # names = ["idle", "walk"] # overrides prefix
+//! # insets = [8, 8, 8, 8] # all frames
+//! [insets] # each frame (overrides grid.insets)
+//! walk_0 = [4, 4, 4, 4]
+//! ```
+//!
+
+use std::error::Error;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::{dx::*, ext::json::Json};
+use crate::ext::graph::Graph;
+use crate::ext::slice::{Insets, SliceMode};
+
+/// AtlasFrame
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasFrame {
+  /// name
+  pub name: String,
+  /// x y w h on the texture (w h are not rotated)
+  pub frame: [i32; 4],
+  /// rotated 90 degrees clockwise on the texture
+  pub rotated: bool,
+  /// x y offset of the trimmed frame in the source size
+  pub offset: [i32; 2],
+  /// w h before trimmed
+  pub source: [i32; 2],
+  /// nine slice insets
+  pub insets: Option<Insets>
+}
+
+/// AtlasFrame
+impl AtlasFrame {
+  /// not trimmed not rotated
+  pub fn new(name: &str, x: i32, y: i32, w: i32, h: i32) -> Self {
+    AtlasFrame{name: name.to_string(), frame: [x, y, w, h], rotated: false,
+      offset: [0, 0], source: [w, h], insets: None}
+  }
+  /// with nine slice insets
+  pub fn with_insets(mut self, insets: Insets) -> Self {
+    self.insets = Some(insets);
+    self
+  }
+  /// x y w h of the area on the texture (swap w h when rotated)
+  pub fn sheet_rect(&self) -> [i32; 4] {
+    let [x, y, w, h] = self.frame;
+    if self.rotated { [x, y, h, w] } else { [x, y, w, h] }
+  }
+  /// trimmed
+  pub fn trimmed(&self) -> bool {
+    self.offset != [0, 0] || self.source != [self.frame[2], self.frame[3]]
+  }
+  /// center of the trimmed frame from the center of the source size
+  pub fn center_offset(&self) -> (f32, f32) {
+    (self.offset[0] as f32 + self.frame[2] as f32 / 2.0
+      - self.source[0] as f32 / 2.0,
+     self.offset[1] as f32 + self.frame[3] as f32 / 2.0
+      - self.source[1] as f32 / 2.0)
+  }
+}
+
+/// AtlasDesc (parsed descriptor)
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasDesc {
+  /// image file name (relative to the descriptor)
+  pub image: Option<String>,
+  /// frames
+  pub frames: Vec<AtlasFrame>
+}
+
+/// AtlasDesc
+impl AtlasDesc {
+  /// load descriptor (.toml grid or TexturePacker .json)
+  pub fn load(n: &str) -> Result<Self, Box<dyn Error>> {
+    let n = n.trim_end_matches('\0');
+    let s = std::fs::read_to_string(n)?;
+    match Path::new(n).extension().and_then(|e| e.to_str()) {
+    Some("toml") => AtlasDesc::from_toml_grid(&s),
+    _ => AtlasDesc::from_json(&s)
+    }
+  }
+  /// TexturePacker JSON (Hash or Array)
+  pub fn from_json(s: &str) -> Result<Self, Box<dyn Error>> {
+    let j = Json::parse(s)?;
+    let image = j.get("meta").and_then(|m| m.get("image"))
+      .and_then(|i| i.as_str()).map(|i| i.to_string());
+    let frames = match j.get("frames") {
+    Some(Json::Obj(o)) => o.iter().map(|(n, f)|
+      frame_from_json(n, f)).collect::<Result<Vec<_>, _>>()?,
+    Some(Json::Arr(a)) => a.iter().map(|f| {
+      let n = f.get("filename").and_then(|n| n.as_str())
+        .ok_or("atlas: frame without filename")?;
+      frame_from_json(n, f)
+    }).collect::<Result<Vec<_>, _>>()?,
+    _ => return Err("atlas: no frames".into())
+    };
+    Ok(AtlasDesc{image, frames})
+  }
+  /// simple TOML grid
+  pub fn from_toml_grid(s: &str) -> Result<Self, Box<dyn Error>> {
+    let t = parse_toml(s)?;
+    let int = |k: &str, d: Option<i32>| -> Result<i32, Box<dyn Error>> {
+      match t.get(k) {
+      None => d.ok_or_else(|| format!("atlas: no {}", k).into()),
+      Some(TomlValue::Int(i)) => Ok(*i as i32),
+      Some(_) => Err(format!("atlas: {} is not integer", k).into())
+      }
+    };
+    let str = |k: &str| match t.get(k) {
+      Some(TomlValue::Str(s)) => Some(s.clone()),
+      _ => None
+    };
+    let image = str("image");
+    let (cw, ch) = (int("grid.cell_w", None)?, int("grid.cell_h", None)?);
+    let (cols, rows) = (int("grid.columns", None)?, int("grid.rows", None)?);
+    let count = int("grid.count", Some(cols * rows))?;
+    let margin = int("grid.margin", Some(0))?;
+    let spacing = int("grid.spacing", Some(0))?;
+    if cw <= 0 || ch <= 0 || cols <= 0 || rows <= 0 {
+      return Err("atlas: grid size must be positive".into())
+    }
+    if count < 0 || count > cols * rows {
+      return Err("atlas: count out of grid".into())
+    }
+    let prefix = str("grid.prefix").unwrap_or("frame_".to_string());
+    let names = match t.get("grid.names") {
+    Some(TomlValue::Arr(a)) => a.clone(),
+    _ => vec![]
+    };
+    let insets = |k: &str| -> Result<Option<Insets>, Box<dyn Error>> {
+      match t.get(k) {
+      None => Ok(None),
+      Some(TomlValue::Ints(v)) if v.len() == 4 => Ok(Some(Insets::new(
+        v[0] as i32, v[1] as i32, v[2] as i32, v[3] as i32))),
+      Some(_) => Err(format!("atlas: {} must be [l, t, r, b]", k).into())
+      }
+    };
+    let all = insets("grid.insets")?;
+    let frames = (0..count).map(|i| {
+      let (c, r) = (i % cols, i / cols);
+      let n = match names.get(i as usize) {
+      Some(n) => n.clone(),
+      None => format!("{}{}", prefix, i)
+      };
+      let mut f = AtlasFrame::new(&n,
+        margin + c * (cw + spacing), margin + r * (ch + spacing), cw, ch);
+      f.insets = insets(&format!("insets.{}", n))?.or(all);
+      Ok(f)
+    }).collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    Ok(AtlasDesc{image, frames})
+  }
+}
+
+/// one frame of TexturePacker JSON
+fn frame_from_json(n: &str, f: &Json) -> Result<AtlasFrame, Box<dyn Error>> {
+  let rect = |j: Option<&Json>, ks: &[&str]| -> Option<Vec<i32>> {
+    j.and_then(|j| ks.iter().map(|k|
+      j.get(k).and_then(|v| v.as_i32())).collect())
+  };
+  let r = rect(f.get("frame"), &["x", "y", "w", "h"])
+    .ok_or_else(|| format!("atlas: bad frame {}", n))?;
+  let rotated = f.get("rotated").and_then(|r| r.as_bool()).unwrap_or(false);
+  let offset = rect(f.get("spriteSourceSize"), &["x", "y"])
+    .unwrap_or(vec![0, 0]);
+  let source = rect(f.get("sourceSize"), &["w", "h"])
+    .unwrap_or(vec![r[2], r[3]]);
+  let insets = match f.get("insets").and_then(|i| i.as_arr()) {
+  None => None,
+  Some(a) => match a.iter().map(|v| v.as_i32()).collect::<Option<Vec<_>>>() {
+    Some(v) if v.len() == 4 => Some(Insets::new(v[0], v[1], v[2], v[3])),
+    _ => return Err(format!("atlas: bad insets {}", n).into())
+    }
+  };
+  Ok(AtlasFrame{name: n.to_string(), frame: [r[0], r[1], r[2], r[3]],
+    rotated, offset: [offset[0], offset[1]], source: [source[0], source[1]],
+    insets})
+}
+
+/// TomlValue (subset)
+#[derive(Debug, Clone, PartialEq)]
+enum TomlValue {
+  /// integer
+  Int(i64),
+  /// string
+  Str(String),
+  /// array of strings
+  Arr(Vec<String>),
+  /// array of integers
+  Ints(Vec<i64>)
+}
+
+/// position of the first '#' out of "string"
+fn comment(l: &str) -> Option<usize> {
+  let mut q = false;
+  l.char_indices().find(|&(_, c)| {
+    if c == '"' { q = !q; }
+    c == '#' && !q
+  }).map(|(p, _)| p)
+}
+
+/// parse TOML subset (key = int / "string" / ["strings"] / [ints], [section])
+/// - result: "section.key" to value
+fn parse_toml(s: &str) -> Result<HashMap<String, TomlValue>, Box<dyn Error>> {
+  let mut m = HashMap::new();
+  let mut sec = String::new();
+  for (i, l) in s.lines().enumerate() {
+    let l = match comment(l) {
+    Some(p) => &l[..p],
+    None => l
+    }.trim();
+    if l.is_empty() { continue; }
+    if l.starts_with('[') && l.ends_with(']') {
+      sec = format!("{}.", l[1..l.len() - 1].trim());
+      continue;
+    }
+    let (k, v) = l.split_once('=')
+      .ok_or_else(|| format!("toml: line {}: expected key = value", i + 1))?;
+    let v = v.trim();
+    let unquote = |q: &str| -> Result<String, Box<dyn Error>> {
+      let q = q.trim();
+      if q.len() >= 2 && q.starts_with('"') && q.ends_with('"') {
+        Ok(q[1..q.len() - 1].to_string())
+      } else {
+        Err(format!("toml: line {}: bad string", i + 1).into())
+      }
+    };
+    let tv = if v.starts_with('[') && v.ends_with(']') {
+      let e = v[1..v.len() - 1].split(',').map(|e| e.trim())
+        .filter(|e| !e.is_empty()).collect::<Vec<_>>();
+      if e.first().is_some_and(|e| !e.starts_with('"')) {
+        TomlValue::Ints(e.into_iter().map(|e| e.parse())
+          .collect::<Result<_, _>>()
+          .map_err(|_| format!("toml: line {}: bad integer", i + 1))?)
+      } else {
+        TomlValue::Arr(e.into_iter().map(unquote).collect::<Result<_, _>>()?)
+      }
+    } else if v.starts_with('"') {
+      TomlValue::Str(unquote(v)?)
+    } else {
+      TomlValue::Int(v.parse()
+        .map_err(|_| format!("toml: line {}: bad integer", i + 1))?)
+    };
+    m.insert(format!("{}{}", sec, k.trim()), tv);
+  }
+  Ok(m)
+}
+
+/// SpriteAtlas
+pub struct SpriteAtlas {
+  /// texture
+  pub tex: Graph,
+  /// frames
+  pub frames: Vec<AtlasFrame>,
+  /// sub handles (DerivationGraph) same order as frames
+  pub graphs: Vec<Graph>,
+  /// name to index
+  pub names: HashMap<String, usize>
+}
+
+/// SpriteAtlas
+impl SpriteAtlas {
+  /// index by name
+  pub fn index(&self, n: &str) -> Option<usize> { self.names.get(n).copied() }
+  /// frame and sub handle at i
+  pub fn at(&self, i: usize) -> Option<(&AtlasFrame, &Graph)> {
+    self.frames.get(i).zip(self.graphs.get(i))
+  }
+  /// frame by name
+  pub fn frame(&self, n: &str) -> Option<&AtlasFrame> {
+    self.index(n).and_then(|i| self.frames.get(i))
+  }
+  /// sub handle by name
+  pub fn get(&self, n: &str) -> Option<&Graph> {
+    self.index(n).and_then(|i| self.graphs.get(i))
+  }
+  /// draw frame i at left top of the source size (restore trim and rotation)
+  /// - None when i is out of range
+  pub fn draw(&self, i: usize, x: i32, y: i32, trans: i32) -> Option<()> {
+    let (f, g) = self.at(i)?;
+    if f.rotated {
+      self.draw_rota(i, x as f32 + f.source[0] as f32 / 2.0,
+        y as f32 + f.source[1] as f32 / 2.0, 1.0, 0.0, trans)
+    } else {
+      g.draw(x + f.offset[0], y + f.offset[1], trans);
+      Some(())
+    }
+  }
+  /// draw frame i rotated around the center of the source size
+  /// - None when i is out of range
+  pub fn draw_rota(&self, i: usize, cx: f32, cy: f32,
+    extrate: f64, angle: f64, trans: i32) -> Option<()> {
+    let (f, g) = self.at(i)?;
+    let (dx, dy) = f.center_offset();
+    let (s, c) = (angle.sin() as f32, angle.cos() as f32);
+    let e = extrate as f32;
+    let x = cx + (dx * c - dy * s) * e;
+    let y = cy + (dx * s + dy * c) * e;
+    let a = if f.rotated { angle - std::f64::consts::FRAC_PI_2 } else { angle };
+    g.draw_rota_f(x, y, extrate, a, trans, FALSE, FALSE);
+    Some(())
+  }
+  /// draw frame i as a nine slice panel by the insets of the descriptor
+  /// (rect: x y w h, rotated frames are not supported)
+  /// - None when i is out of range
+  pub fn draw_nine_slice(&self, i: usize, rect: [i32; 4], mode: SliceMode,
+    trans: i32) -> Option<()> {
+    let (f, g) = self.at(i)?;
+    debug_assert!(!f.rotated, "nine slice of rotated frame {}", f.name);
+    g.draw_nine_slice(rect, &f.insets.unwrap_or_default(), mode, trans);
+    Some(())
+  }
+  /// draw by name (false when not found)
+  pub fn draw_named(&self, n: &str, x: i32, y: i32, trans: i32) -> bool {
+    self.index(n).and_then(|i| self.draw(i, x, y, trans)).is_some()
+  }
+}
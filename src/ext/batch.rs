@@ -0,0 +1,109 @@
+//! batch ext dx bridge for DxLib
+//!
+
+use crate::dx::*;
+use crate::ext::graph::Graph;
+
+/// one vertex of 2D (rhw 1.0)
+fn vt(x: f32, y: f32, c: &COLOR_U8, u: f32, v: f32) -> VERTEX2D {
+  VERTEX2D{pos: VECTOR::new(x, y, 0.0), rhw: 1.0, dif: c.clone(),
+    uv: FLOAT2::new(u, v)}
+}
+
+/// Batch2D (gathers vertices and submits them in one call)
+#[derive(Debug, Clone)]
+pub struct Batch2D {
+  /// DX_PRIMTYPE_TRIANGLELIST or DX_PRIMTYPE_POINTLIST etc
+  pub prim: i32,
+  /// vertices
+  pub vs: Vec<VERTEX2D>
+}
+
+/// Default for Batch2D
+impl Default for Batch2D {
+  /// triangle list
+  fn default() -> Self { Batch2D::new(DX_PRIMTYPE_TRIANGLELIST) }
+}
+
+/// Batch2D
+impl Batch2D {
+  /// constructor (prim: DX_PRIMTYPE_*)
+  pub fn new(prim: i32) -> Self { Batch2D{prim, vs: vec![]} }
+  /// triangle list
+  pub fn triangles() -> Self { Batch2D::default() }
+  /// point list
+  pub fn points() -> Self { Batch2D::new(DX_PRIMTYPE_POINTLIST) }
+  /// number of vertices
+  pub fn len(&self) -> usize { self.vs.len() }
+  /// no vertices
+  pub fn is_empty(&self) -> bool { self.vs.is_empty() }
+  /// remove all vertices (keep capacity)
+  pub fn clear(&mut self) -> &mut Self { self.vs.clear(); self }
+  /// raw vertex (for strip fan line list)
+  pub fn vertex(&mut self, v: VERTEX2D) -> &mut Self {
+    self.vs.push(v);
+    self
+  }
+  /// point (point list)
+  pub fn point(&mut self, x: f32, y: f32, c: &COLOR_U8) -> &mut Self {
+    debug_assert_eq!(self.prim, DX_PRIMTYPE_POINTLIST, "batch point");
+    self.vs.push(vt(x, y, c, 0.0, 0.0));
+    self
+  }
+  /// triangle (CW, vertex color)
+  pub fn tri(&mut self, p: [(f32, f32); 3], c: &COLOR_U8) -> &mut Self {
+    self.tri_uv(p.map(|(x, y)| (x, y, 0.0, 0.0)), [c, c, c])
+  }
+  /// triangle (x y u v and color of each vertex)
+  pub fn tri_uv(&mut self, p: [(f32, f32, f32, f32); 3],
+    c: [&COLOR_U8; 3]) -> &mut Self {
+    debug_assert_eq!(self.prim, DX_PRIMTYPE_TRIANGLELIST, "batch tri");
+    for ((x, y, u, v), c) in p.into_iter().zip(c) {
+      self.vs.push(vt(x, y, c, u, v));
+    }
+    self
+  }
+  /// quad (right bottom not included, vertex color)
+  pub fn quad(&mut self, l: f32, t: f32, r: f32, b: f32,
+    c: &COLOR_U8) -> &mut Self {
+    self.quad_uv([l, t, r, b], [0.0, 0.0, 1.0, 1.0], [c, c, c, c])
+  }
+  /// quad with uv (u0 v0 u1 v1) and color of left top, right top,
+  /// right bottom, left bottom
+  pub fn quad_uv(&mut self, p: [f32; 4], uv: [f32; 4],
+    c: [&COLOR_U8; 4]) -> &mut Self {
+    let [l, t, r, b] = p;
+    let [u0, v0, u1, v1] = uv;
+    let q = [(l, t, u0, v0), (r, t, u1, v0), (r, b, u1, v1), (l, b, u0, v1)];
+    self.tri_uv([q[0], q[1], q[3]], [c[0], c[1], c[3]]);
+    self.tri_uv([q[1], q[2], q[3]], [c[1], c[2], c[3]])
+  }
+  /// pixel as 1x1 quad (triangle list) or point (point list)
+  pub fn pixel(&mut self, x: i32, y: i32, c: &COLOR_U8) -> &mut Self {
+    let (x, y) = (x as f32, y as f32);
+    if self.prim == DX_PRIMTYPE_POINTLIST { self.point(x, y, c) }
+    else { self.quad(x, y, x + 1.0, y + 1.0, c) }
+  }
+  /// submit with graph handle (gh: DX_NONE_GRAPH vertex color only)
+  pub fn draw_handle(&self, gh: i32, trans: i32) -> i32 {
+    if self.vs.is_empty() { return 0 }
+    unsafe { DrawPrimitive2D(self.vs.as_ptr(), self.vs.len() as i32,
+      self.prim, gh, trans) }
+  }
+  /// submit vertex color only
+  pub fn draw(&self, trans: i32) -> i32 {
+    self.draw_handle(DX_NONE_GRAPH, trans)
+  }
+  /// submit textured by g
+  pub fn draw_graph(&self, g: &Graph, trans: i32) -> i32 {
+    self.draw_handle(g.h, trans)
+  }
+  /// submit to the current shaders (set_use_vertex_shader etc)
+  pub fn draw_to_shader(&self) -> i32 {
+    if self.vs.is_empty() { return 0 }
+    let vs = self.vs.iter().map(|v| VERTEX2DSHADER{pos: v.pos.clone(),
+      rhw: v.rhw, dif: v.dif.clone(), spc: COLOR_U8::zeros(),
+      uv: v.uv.clone(), suv: FLOAT2::zeros()}).collect::<Vec<_>>();
+    unsafe { DrawPrimitive2DToShader(vs.as_ptr(), vs.len() as i32, self.prim) }
+  }
+}
@@ -0,0 +1,154 @@
+//! canvas ext dx bridge for DxLib
+//!
+//! float variants are drawn by ...AA (C# DLL does not export ...F)
+//!
+
+use crate::{dx::*, ext::tdx::*};
+
+/// PackColor (COLOR_U8 or packed color by get_color)
+pub trait PackColor {
+  /// packed color for the current screen format
+  fn pack(&self) -> u32;
+}
+
+/// PackColor for u32
+impl PackColor for u32 {
+  /// as is (get_color)
+  fn pack(&self) -> u32 { *self }
+}
+
+/// PackColor for COLOR_U8
+impl PackColor for COLOR_U8 {
+  /// by get_color (alpha is ignored, use set_draw_blend_mode)
+  fn pack(&self) -> u32 {
+    get_color(self.r as i32, self.g as i32, self.b as i32)
+  }
+}
+
+/// Canvas (2D primitives on the draw screen)
+#[derive(Debug, Clone)]
+pub struct Canvas {
+  /// line thickness
+  pub thickness: f32,
+  /// vertices of circle oval round rect (AA)
+  pub posnum: i32
+}
+
+/// Default for Canvas
+impl Default for Canvas {
+  /// thickness 1 posnum 32
+  fn default() -> Self { Canvas{thickness: 1.0, posnum: 32} }
+}
+
+/// Canvas
+impl Canvas {
+  /// constructor
+  pub fn new() -> Self { Canvas::default() }
+  /// with thickness
+  pub fn with_thickness(thickness: f32) -> Self {
+    Canvas{thickness, ..Canvas::default()}
+  }
+  /// thickness for integer variants
+  fn ti(&self) -> i32 { (self.thickness as i32).max(1) }
+
+  /// pixel
+  pub fn pixel(&self, x: i32, y: i32, c: impl PackColor) -> i32 {
+    unsafe { DrawPixel(x, y, c.pack()) }
+  }
+  /// line
+  pub fn line(&self, x1: i32, y1: i32, x2: i32, y2: i32,
+    c: impl PackColor) -> i32 {
+    unsafe { DrawLine(x1, y1, x2, y2, c.pack(), self.ti()) }
+  }
+  /// line (float anti-aliased)
+  pub fn line_aa(&self, x1: f32, y1: f32, x2: f32, y2: f32,
+    c: impl PackColor) -> i32 {
+    unsafe { DrawLineAA(x1, y1, x2, y2, c.pack(), self.thickness) }
+  }
+  /// box (right bottom not included)
+  pub fn rect(&self, l: i32, t: i32, r: i32, b: i32,
+    c: impl PackColor, fill: bool) -> i32 {
+    unsafe {
+      if fill || self.ti() == 1 { DrawBox(l, t, r, b, c.pack(), fill as i32) }
+      else { DrawBoxAA(l as f32, t as f32, r as f32, b as f32,
+        c.pack(), FALSE, self.thickness) }
+    }
+  }
+  /// box frame (1 pixel)
+  pub fn line_box(&self, l: i32, t: i32, r: i32, b: i32,
+    c: impl PackColor) -> i32 {
+    unsafe { DrawLineBox(l, t, r, b, c.pack()) }
+  }
+  /// box (float anti-aliased)
+  pub fn rect_aa(&self, l: f32, t: f32, r: f32, b: f32,
+    c: impl PackColor, fill: bool) -> i32 {
+    unsafe { DrawBoxAA(l, t, r, b, c.pack(), fill as i32, self.thickness) }
+  }
+  /// circle
+  pub fn circle(&self, x: i32, y: i32, r: i32,
+    c: impl PackColor, fill: bool) -> i32 {
+    unsafe { DrawCircle(x, y, r, c.pack(), fill as i32, self.ti()) }
+  }
+  /// circle (float anti-aliased)
+  pub fn circle_aa(&self, x: f32, y: f32, r: f32,
+    c: impl PackColor, fill: bool) -> i32 {
+    unsafe { DrawCircleAA(x, y, r, self.posnum,
+      c.pack(), fill as i32, self.thickness) }
+  }
+  /// oval
+  pub fn oval(&self, x: i32, y: i32, rx: i32, ry: i32,
+    c: impl PackColor, fill: bool) -> i32 {
+    unsafe { DrawOval(x, y, rx, ry, c.pack(), fill as i32, self.ti()) }
+  }
+  /// oval (float anti-aliased)
+  pub fn oval_aa(&self, x: f32, y: f32, rx: f32, ry: f32,
+    c: impl PackColor, fill: bool) -> i32 {
+    unsafe { DrawOvalAA(x, y, rx, ry, self.posnum,
+      c.pack(), fill as i32, self.thickness) }
+  }
+  /// triangle
+  pub fn triangle(&self, p: [(i32, i32); 3],
+    c: impl PackColor, fill: bool) -> i32 {
+    unsafe { DrawTriangle(p[0].0, p[0].1, p[1].0, p[1].1, p[2].0, p[2].1,
+      c.pack(), fill as i32) }
+  }
+  /// triangle (float anti-aliased)
+  pub fn triangle_aa(&self, p: [(f32, f32); 3],
+    c: impl PackColor, fill: bool) -> i32 {
+    unsafe { DrawTriangleAA(p[0].0, p[0].1, p[1].0, p[1].1, p[2].0, p[2].1,
+      c.pack(), fill as i32, self.thickness) }
+  }
+  /// quadrangle (left top, right top, right bottom, left bottom)
+  pub fn quad(&self, p: [(i32, i32); 4],
+    c: impl PackColor, fill: bool) -> i32 {
+    unsafe { DrawQuadrangle(p[0].0, p[0].1, p[1].0, p[1].1,
+      p[2].0, p[2].1, p[3].0, p[3].1, c.pack(), fill as i32) }
+  }
+  /// quadrangle (float anti-aliased)
+  pub fn quad_aa(&self, p: [(f32, f32); 4],
+    c: impl PackColor, fill: bool) -> i32 {
+    unsafe { DrawQuadrangleAA(p[0].0, p[0].1, p[1].0, p[1].1,
+      p[2].0, p[2].1, p[3].0, p[3].1, c.pack(), fill as i32, self.thickness) }
+  }
+  /// rounded rect (rx ry: radius of corners)
+  pub fn round_rect(&self, l: i32, t: i32, r: i32, b: i32, rx: i32, ry: i32,
+    c: impl PackColor, fill: bool) -> i32 {
+    unsafe { DrawRoundRect(l, t, r, b, rx, ry, c.pack(), fill as i32) }
+  }
+  /// rounded rect (float anti-aliased)
+  pub fn round_rect_aa(&self, l: f32, t: f32, r: f32, b: f32,
+    rx: f32, ry: f32, c: impl PackColor, fill: bool) -> i32 {
+    unsafe { DrawRoundRectAA(l, t, r, b, rx, ry, self.posnum,
+      c.pack(), fill as i32, self.thickness) }
+  }
+  /// polyline (closed: connect last to first)
+  pub fn polyline_aa(&self, p: &[(f32, f32)], c: impl PackColor,
+    closed: bool) -> i32 {
+    let c = c.pack();
+    let n = if closed { p.len() } else { p.len().saturating_sub(1) };
+    (0..n).map(|i| {
+      let (a, b) = (p[i], p[(i + 1) % p.len()]);
+      unsafe { DrawLineAA(a.0, a.1, b.0, b.1, c, self.thickness) }
+    }).fold(0, |r, e| if e != 0 { e } else { r })
+  }
+}
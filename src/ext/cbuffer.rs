@@ -0,0 +1,107 @@
+//! cbuffer ext dx bridge for DxLib
+//!
+//! HLSL cbuffer packing rules for typed constant buffers
+//! - a member does not straddle a 16 byte register
+//! - a member larger than 16 bytes starts on a register
+//! - each element of an array starts on a register (use [FLOAT4; n])
+//! - the size of a cbuffer is a multiple of 16 bytes
+//!
+//! ```ignore
+//! cbuffer! {
+//!   pub struct CamLight {
+//!     pub cam_pos4: FLOAT4,
+//!     pub cam_lat4: FLOAT4,
+//!     pub r: [FLOAT4; 2]
+//!   }
+//! }
+//! let mut cb = dx.create_typed_constant_buffer::<CamLight>(8);
+//! cb.map().get_mut().cam_pos4 = p; // updated on drop
+//! ```
+//!
+
+use crate::dx::*;
+
+/// size of a register
+pub const REGISTER: usize = 16;
+
+/// HlslType (Rust type of a cbuffer member)
+/// # Safety
+/// SIZE and ELEM must match the layout of the HLSL type
+pub unsafe trait HlslType {
+  /// size in bytes
+  const SIZE: usize;
+  /// size of an element (0: not an array)
+  const ELEM: usize = 0;
+}
+
+/// CbLayout (struct checked by cbuffer!)
+/// # Safety
+/// the layout must follow the HLSL packing rules
+pub unsafe trait CbLayout: HlslType + 'static {
+  /// name offset size of the members (empty: raw)
+  const FIELDS: &'static [(&'static str, usize, usize)] = &[];
+}
+
+unsafe impl HlslType for f32 { const SIZE: usize = 4; }
+unsafe impl HlslType for i32 { const SIZE: usize = 4; }
+unsafe impl HlslType for u32 { const SIZE: usize = 4; }
+/// float2
+unsafe impl HlslType for FLOAT2 { const SIZE: usize = 8; }
+/// float3
+unsafe impl HlslType for VECTOR { const SIZE: usize = 12; }
+/// float4
+unsafe impl HlslType for FLOAT4 { const SIZE: usize = 16; }
+/// float4
+unsafe impl HlslType for COLOR_F { const SIZE: usize = 16; }
+/// float4x4
+unsafe impl HlslType for MATRIX { const SIZE: usize = 64; }
+/// array (elements are not packed)
+unsafe impl<T: HlslType, const N: usize> HlslType for [T; N] {
+  const SIZE: usize = T::SIZE * N;
+  const ELEM: usize = T::SIZE;
+}
+
+/// raw buffer of FLOAT4
+unsafe impl CbLayout for FLOAT4 {}
+
+/// member at offset follows the packing rules
+#[allow(clippy::manual_is_multiple_of)] // is_multiple_of needs Rust 1.87
+pub const fn hlsl_field_ok(offset: usize, size: usize, elem: usize) -> bool {
+  if size == 0 { return false }
+  if elem > 0 { return offset % REGISTER == 0 && elem % REGISTER == 0 }
+  if size >= REGISTER { return offset % REGISTER == 0 }
+  offset / REGISTER == (offset + size - 1) / REGISTER
+}
+
+/// declare a #[repr(C)] struct and check the HLSL layout at compile time
+#[macro_export]
+macro_rules! cbuffer {
+  ($(#[$m:meta])* $v:vis struct $n:ident {
+    $($(#[$fm:meta])* $fv:vis $f:ident: $t:ty),* $(,)?
+  }) => {
+    $(#[$m])*
+    #[derive(Debug, Clone)]
+    #[repr(C)]
+    $v struct $n { $($(#[$fm])* $fv $f: $t),* }
+    unsafe impl $crate::ext::cbuffer::HlslType for $n {
+      const SIZE: usize = ::core::mem::size_of::<$n>();
+    }
+    unsafe impl $crate::ext::cbuffer::CbLayout for $n {
+      const FIELDS: &'static [(&'static str, usize, usize)] = &[
+        $((stringify!($f), ::core::mem::offset_of!($n, $f),
+          ::core::mem::size_of::<$t>())),*];
+    }
+    #[allow(clippy::manual_is_multiple_of)]
+    const _: () = {
+      $(assert!($crate::ext::cbuffer::hlsl_field_ok(
+        ::core::mem::offset_of!($n, $f),
+        <$t as $crate::ext::cbuffer::HlslType>::SIZE,
+        <$t as $crate::ext::cbuffer::HlslType>::ELEM),
+        concat!("cbuffer ", stringify!($n), ".", stringify!($f),
+          " straddles a register"));)*
+      assert!(::core::mem::size_of::<$n>() % $crate::ext::cbuffer::REGISTER
+        == 0, concat!("cbuffer ", stringify!($n),
+          " size is not a multiple of 16"));
+    };
+  }
+}
@@ -0,0 +1,176 @@
+//! constants ext dx bridge for DxLib
+//!
+//! named shader constants for DX9 (registers c i b) resolved once
+//!
+//! ```ignore
+//! let pc = ShaderConstants::new(&shp, &["g_Reg0", "g_Mtx", "g_Arr"])?;
+//! pc.set_f("g_Reg0", &FLOAT4::new(1.0, 1.0, 1.0, 1.0))?;
+//! pc.set_mtx("g_Mtx", &m)?;
+//! pc.set_f_array("g_Arr", &pc.defaults("g_Arr")?)?;
+//! ```
+//!
+//! names are resolved again when the handle of the shader is changed
+//! (hot reload)
+//!
+
+use std::error::Error;
+use std::cell::{Ref, RefCell};
+
+use crate::{dx::*, ext::tdx::*};
+
+/// ConstVar
+#[derive(Debug, Clone)]
+pub struct ConstVar {
+  /// name
+  pub name: String,
+  /// first register
+  pub index: i32,
+  /// number of registers
+  pub count: i32,
+  /// default values (copied at resolve, empty when the shader has no default)
+  pub defaults: Vec<FLOAT4>
+}
+
+/// ShaderConstants (valid while the shader is alive)
+pub struct ShaderConstants<'a, S: Ts> {
+  /// shader
+  pub sh: &'a S,
+  /// names (without terminating 0)
+  names: Vec<String>,
+  /// handle resolved with and resolved names
+  cache: RefCell<(i32, Vec<ConstVar>)>
+}
+
+/// resolve names on the shader handle
+fn resolve<S: Ts>(sh: &S, names: &[String])
+  -> Result<Vec<ConstVar>, Box<dyn Error>> {
+  names.iter().map(|n| {
+    let l = n.len();
+    let index = sh.get_const_index_to_shader_with_str_len(n, l);
+    if index < 0 {
+      return Err(format!("shader constants: no {} in {:08x}",
+        n, sh.handle()).into())
+    }
+    let count = sh.get_const_count_to_shader_with_str_len(n, l);
+    let dp = sh.get_const_default_param_f_to_shader_with_str_len(n, l);
+    let defaults = if dp.is_null() || count <= 0 { vec![] } else {
+      unsafe { std::slice::from_raw_parts(dp, count as usize) }.to_vec() };
+    Ok(ConstVar{name: n.clone(), index, count, defaults})
+  }).collect()
+}
+
+/// ShaderConstants
+impl<'a, S: Ts> ShaderConstants<'a, S> {
+  /// resolve names (without terminating 0) of vertex or pixel shader
+  pub fn new(sh: &'a S, names: &[&str]) -> Result<Self, Box<dyn Error>> {
+    let ts = sh.shader_type();
+    if ts != DX_SHADERTYPE_VERTEX && ts != DX_SHADERTYPE_PIXEL {
+      return Err(format!("shader constants: shader type {} (DX9)", ts).into())
+    }
+    let names: Vec<String> = names.iter()
+      .map(|n| n.trim_end_matches('\0').to_string()).collect();
+    let vars = resolve(sh, &names)?;
+    Ok(ShaderConstants{sh, names, cache: RefCell::new((sh.handle(), vars))})
+  }
+  /// resolved variables (resolved again when the shader handle is changed)
+  pub fn vars(&self) -> Result<Ref<'_, [ConstVar]>, Box<dyn Error>> {
+    let h = self.sh.handle();
+    if self.cache.borrow().0 != h {
+      let vars = resolve(self.sh, &self.names)?;
+      *self.cache.borrow_mut() = (h, vars);
+    }
+    Ok(Ref::map(self.cache.borrow(), |c| c.1.as_slice()))
+  }
+  /// resolved variable
+  fn var(&self, n: &str) -> Result<Ref<'_, ConstVar>, Box<dyn Error>> {
+    Ref::filter_map(self.vars()?, |v| v.iter().find(|v| v.name == n))
+      .map_err(|_| format!("shader constants: {} is not resolved", n).into())
+  }
+  /// first register and number of registers
+  pub fn get(&self, n: &str) -> Result<(i32, i32), Box<dyn Error>> {
+    self.var(n).map(|v| (v.index, v.count))
+  }
+  /// first register of the variable that has c registers at least
+  fn fit(&self, n: &str, c: usize) -> Result<i32, Box<dyn Error>> {
+    let (i, count) = self.get(n)?;
+    if (count as usize) < c {
+      return Err(format!("shader constants: {} has {} registers not {}",
+        n, count, c).into())
+    }
+    Ok(i)
+  }
+  /// default values (copied, empty when the shader has no default)
+  pub fn defaults(&self, n: &str) -> Result<Vec<FLOAT4>, Box<dyn Error>> {
+    Ok(self.var(n)?.defaults.clone())
+  }
+  /// vertex shader
+  fn vs(&self) -> bool { self.sh.shader_type() == DX_SHADERTYPE_VERTEX }
+  /// float4
+  pub fn set_f(&self, n: &str, p: &FLOAT4) -> Result<(), Box<dyn Error>> {
+    let i = self.fit(n, 1)?;
+    unsafe { if self.vs() { SetVSConstF(i, p.clone()) }
+      else { SetPSConstF(i, p.clone()) }; }
+    Ok(())
+  }
+  /// float4x4 (4 registers)
+  pub fn set_mtx(&self, n: &str, m: &MATRIX) -> Result<(), Box<dyn Error>> {
+    let i = self.fit(n, 4)?;
+    unsafe { if self.vs() { SetVSConstFMtx(i, m.clone()) }
+      else { SetPSConstFMtx(i, m.clone()) }; }
+    Ok(())
+  }
+  /// float4x4 transposed (4 registers)
+  pub fn set_mtx_t(&self, n: &str, m: &MATRIX) -> Result<(), Box<dyn Error>> {
+    let i = self.fit(n, 4)?;
+    unsafe { if self.vs() { SetVSConstFMtxT(i, m.clone()) }
+      else { SetPSConstFMtxT(i, m.clone()) }; }
+    Ok(())
+  }
+  /// int4
+  pub fn set_i(&self, n: &str, p: &INT4) -> Result<(), Box<dyn Error>> {
+    let i = self.fit(n, 1)?;
+    unsafe { if self.vs() { SetVSConstI(i, p.clone()) }
+      else { SetPSConstI(i, p.clone()) }; }
+    Ok(())
+  }
+  /// bool
+  pub fn set_b(&self, n: &str, p: bool) -> Result<(), Box<dyn Error>> {
+    let i = self.fit(n, 1)?;
+    let p = if p { TRUE } else { FALSE };
+    unsafe { if self.vs() { SetVSConstB(i, p) } else { SetPSConstB(i, p) }; }
+    Ok(())
+  }
+  /// float4 array (not longer than the registers)
+  pub fn set_f_array(&self, n: &str, p: &[FLOAT4])
+    -> Result<(), Box<dyn Error>> {
+    let (i, c) = (self.fit(n, p.len())?, p.len() as i32);
+    unsafe { if self.vs() { SetVSConstFArray(i, p.as_ptr(), c) }
+      else { SetPSConstFArray(i, p.as_ptr(), c) }; }
+    Ok(())
+  }
+  /// int4 array (not longer than the registers)
+  pub fn set_i_array(&self, n: &str, p: &[INT4])
+    -> Result<(), Box<dyn Error>> {
+    let (i, c) = (self.fit(n, p.len())?, p.len() as i32);
+    unsafe { if self.vs() { SetVSConstIArray(i, p.as_ptr(), c) }
+      else { SetPSConstIArray(i, p.as_ptr(), c) }; }
+    Ok(())
+  }
+  /// bool array (not longer than the registers)
+  pub fn set_b_array(&self, n: &str, p: &[bool])
+    -> Result<(), Box<dyn Error>> {
+    let i = self.fit(n, p.len())?;
+    let b: Vec<i32> = p.iter().map(|f| if *f { TRUE } else { FALSE })
+      .collect();
+    unsafe { if self.vs() { SetVSConstBArray(i, b.as_ptr(), b.len() as i32) }
+      else { SetPSConstBArray(i, b.as_ptr(), b.len() as i32) }; }
+    Ok(())
+  }
+  /// release float4 registers of n (use the default values)
+  pub fn reset_f(&self, n: &str) -> Result<(), Box<dyn Error>> {
+    let (i, c) = self.get(n)?;
+    unsafe { if self.vs() { ResetVSConstF(i, c) }
+      else { ResetPSConstF(i, c) }; }
+    Ok(())
+  }
+}
@@ -0,0 +1,217 @@
+//! filter ext dx bridge for DxLib
+//!
+
+use crate::dx::*;
+use crate::ext::graph::{Screen, Graph};
+
+/// Filter (GraphFilter parameters)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+  /// monotone cb cr -255-255
+  Mono{cb: i32, cr: i32},
+  /// gaussian blur pixel_width 8 16 32, param 100 = about 1 pixel
+  Gauss{pixel_width: i32, param: i32},
+  /// down scale div 2 4 8
+  DownScale{div: i32},
+  /// fill pixels brighter (greater) or darker (not greater) than param
+  BrightClip{greater: bool, param: i32, fill: bool, color: u32, alpha: i32},
+  /// bright scale min max 0-255
+  BrightScale{min: i32, max: i32},
+  /// hue -180-180 (absolute 0-360), saturation -255-, bright -255-255
+  Hsb{absolute: bool, hue: i32, saturation: i32, bright: i32},
+  /// invert
+  Invert,
+  /// level min max 0-255, gamma 100 = 1.0, after_min after_max 0-255
+  Level{min: i32, max: i32, gamma: i32, after_min: i32, after_max: i32},
+  /// two color by threshold 0-255
+  TwoColor{threshold: i32,
+    low_color: u32, low_alpha: i32, high_color: u32, high_alpha: i32},
+  /// gradient map (map: graph handle)
+  GradientMap{map: i32, reverse: bool},
+  /// replace target color with color
+  Replacement{target: COLOR_U8, color: COLOR_U8},
+  /// to premultiplied alpha
+  PremulAlpha,
+  /// from premultiplied alpha
+  InterpAlpha,
+  /// bicubic scale (filter_to only)
+  BicubicScale{w: i32, h: i32},
+  /// lanczos3 scale (filter_to only)
+  Lanczos3Scale{w: i32, h: i32}
+}
+
+/// Filter
+impl Filter {
+  /// filter type and params
+  pub fn params(&self) -> (i32, [i32; 10]) {
+    let mut p = [0i32; 10];
+    let t = match self {
+    Filter::Mono{cb, cr} => {
+      p[..2].copy_from_slice(&[*cb, *cr]);
+      DX_GRAPH_FILTER_MONO
+    },
+    Filter::Gauss{pixel_width, param} => {
+      p[..2].copy_from_slice(&[*pixel_width, *param]);
+      DX_GRAPH_FILTER_GAUSS
+    },
+    Filter::DownScale{div} => { p[0] = *div; DX_GRAPH_FILTER_DOWN_SCALE },
+    Filter::BrightClip{greater, param, fill, color, alpha} => {
+      let cmp = if *greater { DX_CMP_GREATER } else { DX_CMP_LESS };
+      p[..5].copy_from_slice(
+        &[cmp, *param, *fill as i32, *color as i32, *alpha]);
+      DX_GRAPH_FILTER_BRIGHT_CLIP
+    },
+    Filter::BrightScale{min, max} => {
+      p[..2].copy_from_slice(&[*min, *max]);
+      DX_GRAPH_FILTER_BRIGHT_SCALE
+    },
+    Filter::Hsb{absolute, hue, saturation, bright} => {
+      p[..4].copy_from_slice(&[*absolute as i32, *hue, *saturation, *bright]);
+      DX_GRAPH_FILTER_HSB
+    },
+    Filter::Invert => DX_GRAPH_FILTER_INVERT,
+    Filter::Level{min, max, gamma, after_min, after_max} => {
+      p[..5].copy_from_slice(&[*min, *max, *gamma, *after_min, *after_max]);
+      DX_GRAPH_FILTER_LEVEL
+    },
+    Filter::TwoColor{threshold, low_color, low_alpha, high_color, high_alpha}
+      => {
+      p[..5].copy_from_slice(&[*threshold,
+        *low_color as i32, *low_alpha, *high_color as i32, *high_alpha]);
+      DX_GRAPH_FILTER_TWO_COLOR
+    },
+    Filter::GradientMap{map, reverse} => {
+      p[..2].copy_from_slice(&[*map, *reverse as i32]);
+      DX_GRAPH_FILTER_GRADIENT_MAP
+    },
+    Filter::Replacement{target: s, color: c} => {
+      p[..8].copy_from_slice(&[s.r, s.g, s.b, s.a, c.r, c.g, c.b, c.a]
+        .map(|v| v as i32));
+      DX_GRAPH_FILTER_REPLACEMENT
+    },
+    Filter::PremulAlpha => DX_GRAPH_FILTER_PREMUL_ALPHA,
+    Filter::InterpAlpha => DX_GRAPH_FILTER_INTERP_ALPHA,
+    Filter::BicubicScale{w, h} => {
+      p[..2].copy_from_slice(&[*w, *h]);
+      DX_GRAPH_FILTER_BICUBIC_SCALE
+    },
+    Filter::Lanczos3Scale{w, h} => {
+      p[..2].copy_from_slice(&[*w, *h]);
+      DX_GRAPH_FILTER_LANCZOS3_SCALE
+    }
+    };
+    (t, p)
+  }
+}
+
+/// Blend (GraphBlend parameters)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Blend {
+  /// normal
+  Normal,
+  /// select each channel from DX_RGBA_SELECT_SRC_R DX_RGBA_SELECT_BLEND_R etc
+  RgbaSelectMix{r: i32, g: i32, b: i32, a: i32},
+  /// multiple
+  Multiple,
+  /// difference
+  Difference,
+  /// add
+  Add,
+  /// screen
+  Screen,
+  /// overlay
+  Overlay,
+  /// dodge
+  Dodge,
+  /// burn
+  Burn,
+  /// darken
+  Darken,
+  /// lighten
+  Lighten,
+  /// soft light
+  SoftLight,
+  /// hard light
+  HardLight,
+  /// exclusion
+  Exclusion,
+  /// normal with alpha channel
+  NormalAlphaCh,
+  /// add with alpha channel
+  AddAlphaCh,
+  /// multiple alpha only
+  MultipleAOnly
+}
+
+/// Blend
+impl Blend {
+  /// blend type and params
+  pub fn params(&self) -> (i32, [i32; 10]) {
+    let mut p = [0i32; 10];
+    let t = match self {
+    Blend::Normal => DX_GRAPH_BLEND_NORMAL,
+    Blend::RgbaSelectMix{r, g, b, a} => {
+      p[..4].copy_from_slice(&[*r, *g, *b, *a]);
+      DX_GRAPH_BLEND_RGBA_SELECT_MIX
+    },
+    Blend::Multiple => DX_GRAPH_BLEND_MULTIPLE,
+    Blend::Difference => DX_GRAPH_BLEND_DIFFERENCE,
+    Blend::Add => DX_GRAPH_BLEND_ADD,
+    Blend::Screen => DX_GRAPH_BLEND_SCREEN,
+    Blend::Overlay => DX_GRAPH_BLEND_OVERLAY,
+    Blend::Dodge => DX_GRAPH_BLEND_DODGE,
+    Blend::Burn => DX_GRAPH_BLEND_BURN,
+    Blend::Darken => DX_GRAPH_BLEND_DARKEN,
+    Blend::Lighten => DX_GRAPH_BLEND_LIGHTEN,
+    Blend::SoftLight => DX_GRAPH_BLEND_SOFTLIGHT,
+    Blend::HardLight => DX_GRAPH_BLEND_HARDLIGHT,
+    Blend::Exclusion => DX_GRAPH_BLEND_EXCLUSION,
+    Blend::NormalAlphaCh => DX_GRAPH_BLEND_NORMAL_ALPHACH,
+    Blend::AddAlphaCh => DX_GRAPH_BLEND_ADD_ALPHACH,
+    Blend::MultipleAOnly => DX_GRAPH_BLEND_MULTIPLE_A_ONLY
+    };
+    (t, p)
+  }
+}
+
+/// filter and blend for Graph
+impl Graph {
+  /// apply filter to self
+  pub fn filter(&self, f: &Filter) -> i32 {
+    let (t, p) = f.params();
+    unsafe { GraphFilterS(self.h, t,
+      p[0], p[1], p[2], p[3], p[4], p[5], p[6], p[7], p[8], p[9]) }
+  }
+  /// apply filter and write to dst (scale filters need dst of the size)
+  pub fn filter_to(&self, dst: &Graph, f: &Filter) -> i32 {
+    let (t, p) = f.params();
+    unsafe { GraphFilterBltS(self.h, dst.h, t,
+      p[0], p[1], p[2], p[3], p[4], p[5], p[6], p[7], p[8], p[9]) }
+  }
+  /// blend g into self (ratio 0-255)
+  pub fn blend_with(&self, g: &Graph, ratio: i32, b: &Blend) -> i32 {
+    let (t, p) = b.params();
+    unsafe { GraphBlendS(self.h, g.h, ratio, t,
+      p[0], p[1], p[2], p[3], p[4], p[5], p[6], p[7], p[8], p[9]) }
+  }
+  /// blend g with self and write to dst (ratio 0-255)
+  pub fn blend_to(&self, g: &Graph, dst: &Graph, ratio: i32, b: &Blend) -> i32 {
+    let (t, p) = b.params();
+    unsafe { GraphBlendBltS(self.h, g.h, dst.h, ratio, t,
+      p[0], p[1], p[2], p[3], p[4], p[5], p[6], p[7], p[8], p[9]) }
+  }
+}
+
+/// filter and blend for Screen (render target)
+impl Screen {
+  /// apply filter to self
+  pub fn filter(&self, f: &Filter) -> i32 { self.graph().filter(f) }
+  /// apply filter and write to dst
+  pub fn filter_to(&self, dst: &Screen, f: &Filter) -> i32 {
+    self.graph().filter_to(&dst.graph(), f)
+  }
+  /// blend g into self (ratio 0-255)
+  pub fn blend_with(&self, g: &Graph, ratio: i32, b: &Blend) -> i32 {
+    self.graph().blend_with(g, ratio, b)
+  }
+}
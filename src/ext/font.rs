@@ -1,6 +1,8 @@
 //! font ext dx bridge for DxLib
 //!
 
+use std::ffi::c_void;
+
 use crate::{dx::*, ext::tdx::*};
 
 /// Font
@@ -45,6 +47,11 @@ impl Font {
   pub fn load_data(n: &String) -> Self {
     Font{d: true, h: unsafe { LoadFontDataToHandle(n.as_ptr(), 0) } }
   }
+  /// from dft file image (pre convert by CreateDXFontData.exe)
+  pub fn from_bytes(b: &[u8]) -> Self {
+    Font{d: true, h: unsafe { LoadFontDataFromMemToHandle(
+      b.as_ptr() as *const c_void, b.len() as i32, 0) } }
+  }
   /// draw string
   pub fn draw_string(&self, x: i32, y: i32, s: &String, c: u32, e: u32, v: i32) {
     unsafe { DrawStringToHandle(x, y, s.as_ptr(), c, self.h, e, v); }
@@ -1,7 +1,37 @@
 //! graph ext dx bridge for DxLib
 //!
 
+use std::error::Error;
+use std::ffi::c_void;
+
 use crate::{dx::*, ext::tdx::*};
+use crate::ext::soft::{SoftImage, rgba_len};
+
+/// ImageFormat (for save)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+  /// bmp
+  Bmp,
+  /// png (compression level 0-9 or -1: default)
+  Png(i32),
+  /// jpeg (quality 0-100, sample2x1)
+  Jpeg(i32, i32)
+}
+
+/// ImageFormat
+impl ImageFormat {
+  /// by extension (png: -1, jpeg: 80 TRUE)
+  pub fn from_path(n: &str) -> Option<Self> {
+    let n = n.trim_end_matches('\0');
+    let e = std::path::Path::new(n).extension()?.to_str()?.to_lowercase();
+    match e.as_str() {
+    "bmp" => Some(ImageFormat::Bmp),
+    "png" => Some(ImageFormat::Png(-1)),
+    "jpg" | "jpeg" => Some(ImageFormat::Jpeg(80, TRUE)),
+    _ => None
+    }
+  }
+}
 
 /// Screen
 pub struct Screen {
@@ -39,10 +69,77 @@ impl Screen {
   pub fn make(xsz: i32, ysz: i32, trans: i32) -> Self {
     Screen{d: true, h: unsafe { MakeScreen(xsz, ysz, trans) } }
   }
+  /// graph (not disposed) to draw filter or set to shader
+  pub fn graph(&self) -> Graph { Graph{d: false, h: self.h} }
+  /// back buffer (not disposed)
+  pub fn back() -> Self { Screen{d: false, h: DX_SCREEN_BACK} }
   /// set draw
   pub fn set_draw(&self) {
     unsafe { SetDrawScreen(self.h); }
   }
+  /// call f(w, h) while self is the draw screen then restore
+  /// (SetDrawScreen resets camera settings when the screen is changed,
+  /// view projection viewport transforms are restored with the screen)
+  pub fn with_draw<R>(&self, f: impl FnOnce(i32, i32) -> R) -> R {
+    let prev = unsafe { GetDrawScreen() };
+    let cam = if prev == self.h { None } else {
+      let c = get_transforms();
+      self.set_draw();
+      Some(c)
+    };
+    let mut w = 0i32;
+    let mut h = 0i32;
+    unsafe { GetDrawScreenSize(&mut w as *mut i32, &mut h as *mut i32); }
+    let r = f(w, h);
+    if let Some(c) = cam {
+      unsafe { SetDrawScreen(prev); }
+      set_transforms(&c);
+    }
+    r
+  }
+  /// save whole screen to file
+  pub fn save(&self, n: &String, fmt: ImageFormat) -> i32 {
+    self.with_draw(|w, h| unsafe {
+      match fmt {
+      ImageFormat::Bmp => SaveDrawScreenToBMP(0, 0, w, h, n.as_ptr()),
+      ImageFormat::Png(c) => SaveDrawScreenToPNG(0, 0, w, h, n.as_ptr(), c),
+      ImageFormat::Jpeg(q, s) =>
+        SaveDrawScreenToJPEG(0, 0, w, h, n.as_ptr(), q, s)
+      }
+    })
+  }
+  /// copy whole screen to new soft image
+  pub fn capture(&self) -> SoftImage {
+    self.with_draw(|w, h| {
+      let si = SoftImage::make(w, h);
+      unsafe { GetDrawScreenSoftImage(0, 0, w, h, si.h); }
+      si
+    })
+  }
+  /// copy whole screen to r g b a bytes (w * h * 4)
+  pub fn capture_rgba(&self) -> Vec<u8> {
+    self.capture().to_rgba()
+  }
+}
+
+/// view projection viewport transforms
+fn get_transforms() -> [MATRIX; 3] {
+  let mut m = [MATRIX::identity(), MATRIX::identity(), MATRIX::identity()];
+  unsafe {
+    GetTransformToViewMatrix(&mut m[0] as *mut MATRIX);
+    GetTransformToProjectionMatrix(&mut m[1] as *mut MATRIX);
+    GetTransformToViewportMatrix(&mut m[2] as *mut MATRIX);
+  }
+  m
+}
+
+/// set view projection viewport transforms
+fn set_transforms(m: &[MATRIX; 3]) {
+  unsafe {
+    SetTransformToView(&m[0] as *const MATRIX);
+    SetTransformToProjection(&m[1] as *const MATRIX);
+    SetTransformToViewport(&m[2] as *const MATRIX);
+  }
 }
 
 /// Graph
@@ -86,6 +183,38 @@ impl Graph {
   pub fn load(n: &String) -> Self {
     Graph{d: true, h: unsafe { LoadGraph(n.as_ptr()) } }
   }
+  /// from png bmp jpeg etc file image (include_bytes! etc)
+  pub fn from_bytes(b: &[u8]) -> Self {
+    Graph{d: true, h: unsafe { CreateGraphFromMem(
+      b.as_ptr() as *const c_void, b.len() as i32, NULL, 0, TRUE, FALSE) } }
+  }
+  /// from r g b a bytes (w * h * 4) without changing draw screen
+  pub fn from_rgba(w: i32, h: i32, rgba: &[u8])
+    -> Result<Self, Box<dyn Error>> {
+    if rgba_len(w, h) != Some(rgba.len()) {
+      return Err(format!("graph: {} bytes for rgba {}x{}",
+        rgba.len(), w, h).into())
+    }
+    let mut si = SoftImage::make(w, h);
+    if si.h == -1 || si.write_rgba(rgba) == -1 {
+      return Err(format!("graph: soft image {}x{}", w, h).into())
+    }
+    let g = si.to_graph();
+    if g.h == -1 { return Err(format!("graph: rgba {}x{}", w, h).into()) }
+    Ok(g)
+  }
+  /// overwrite by r g b a bytes (the same size as the graph)
+  pub fn update_from_rgba(&self, rgba: &[u8]) -> i32 {
+    let (w, h) = self.get_size();
+    if rgba_len(w, h) != Some(rgba.len()) { return -1 }
+    let mut si = SoftImage::make(w, h);
+    if si.write_rgba(rgba) == -1 { return -1 }
+    unsafe { ReCreateGraphFromSoftImage(si.h, self.h) }
+  }
+  /// derivation (sub rect shares the texture of src)
+  pub fn derivation(src: &Graph, srcx: i32, srcy: i32, w: i32, h: i32) -> Self {
+    Graph{d: true, h: unsafe { DerivationGraph(srcx, srcy, w, h, src.h) } }
+  }
   /// clipping (use SetRestoreGraphCallback to recover full screen)
   /// - left, top, right + 1, bottom + 1
   /// - use_client_flag: default TRUE
@@ -112,6 +241,13 @@ impl Graph {
       DrawRotaGraph(x, y, extrate, angle, self.h, trans, reversex, reversey);
     }
   }
+  /// draw rotate (float center)
+  pub fn draw_rota_f(&self, x: f32, y: f32, extrate: f64, angle: f64,
+    trans: i32, reversex: i32, reversey: i32) {
+    unsafe {
+      DrawRotaGraphF(x, y, extrate, angle, self.h, trans, reversex, reversey);
+    }
+  }
   /// draw modi
   pub fn draw_modi(&self, xlt: i32, ylt: i32, xrt: i32, yrt: i32,
     xrb: i32, yrb: i32, xlb: i32, ylb: i32, trans: i32) {
@@ -145,3 +281,135 @@ impl Graph {
     (w, h)
   }
 }
+
+/// Mask
+pub struct Mask {
+  /// to be disposed
+  pub d: bool,
+  /// handle
+  pub h: i32
+}
+
+/// Tr for Mask
+impl Tr for Mask {
+  /// as mask
+  fn as_mask(&self) -> Mask { Mask{d: false, h: self.h} }
+
+  /// handle
+  fn handle(&self) -> i32 { self.h }
+  /// dispose
+  fn dispose(&mut self) {
+    if self.d && self.h != 0 {
+      unsafe { DeleteMask(self.h); }
+      self.h = 0;
+    }
+  }
+}
+
+/// Drop for Mask
+impl Drop for Mask {
+  /// drop
+  fn drop(&mut self) { self.dispose(); }
+}
+
+/// Mask
+impl Mask {
+  /// empty mask
+  pub fn make(xsz: i32, ysz: i32) -> Self {
+    Mask{d: true, h: unsafe { MakeMask(xsz, ysz) } }
+  }
+  /// load from file
+  pub fn load(n: &String) -> Self {
+    Mask{d: true, h: unsafe { LoadMask(n.as_ptr()) } }
+  }
+  /// get size
+  pub fn get_size(&self) -> (i32, i32) {
+    let mut w = 0i32;
+    let mut h = 0i32;
+    unsafe { GetMaskSize(&mut w as *mut i32, &mut h as *mut i32, self.h); }
+    (w, h)
+  }
+  /// set data (1 byte per pixel w * h, 0: transparent others: masked)
+  pub fn set_data(&self, w: i32, h: i32, data: &[u8]) -> i32 {
+    if data.len() < (w * h) as usize { return -1 }
+    unsafe {
+      SetDataToMask(w, h, data.as_ptr() as *const c_void, self.h)
+    }
+  }
+  /// draw to mask screen
+  /// - trans_mode: DX_MASKTRANS_WHITE DX_MASKTRANS_BLACK DX_MASKTRANS_NONE
+  pub fn draw(&self, x: i32, y: i32, trans_mode: i32) -> i32 {
+    unsafe { DrawMask(x, y, self.h, trans_mode) }
+  }
+  /// draw tiled to mask screen
+  pub fn draw_fill(&self, l: i32, t: i32, r: i32, b: i32) -> i32 {
+    unsafe { DrawFillMask(l, t, r, b, self.h) }
+  }
+}
+
+/// MaskScope (use mask screen until dropped, create_mask_screen before)
+/// - the use flag is read from DxLib at new and restored on drop
+/// - reverse flag and mask graph are not readable from DxLib:
+///   restored to the outer scope (nested) or to FALSE and no graph
+pub struct MaskScope {
+  /// reverse flag of this scope
+  pub reverse: i32,
+  /// graph and channel used as mask screen by this scope
+  pub g: Option<(i32, i32)>,
+  /// use flag reverse flag graph channel before new
+  prev: (i32, i32, Option<(i32, i32)>)
+}
+
+/// MaskScope
+impl MaskScope {
+  /// use mask screen
+  /// - reverse: TRUE draw only where masked
+  pub fn new(reverse: i32) -> Self {
+    MaskScope::open(None, reverse, None)
+  }
+  /// use a channel of the graph (or screen) as mask screen
+  /// - ch: DX_MASKGRAPH_CH_A DX_MASKGRAPH_CH_R etc
+  pub fn graph(g: &Graph, ch: i32, reverse: i32) -> Self {
+    MaskScope::open(None, reverse, Some((g.h, ch)))
+  }
+  /// nested scope (restores the settings of self on drop)
+  pub fn nested(&self, reverse: i32) -> Self {
+    MaskScope::open(Some(self), reverse, None)
+  }
+  /// nested scope using a channel of the graph as mask screen
+  pub fn nested_graph(&self, g: &Graph, ch: i32, reverse: i32) -> Self {
+    MaskScope::open(Some(self), reverse, Some((g.h, ch)))
+  }
+  /// set mask settings
+  fn open(outer: Option<&MaskScope>, reverse: i32, g: Option<(i32, i32)>)
+    -> Self {
+    let prev = (unsafe { GetUseMaskScreenFlag() },
+      outer.map_or(FALSE, |o| o.reverse), outer.and_then(|o| o.g));
+    unsafe {
+      SetMaskReverseEffectFlag(reverse);
+      if let Some((gh, ch)) = g {
+        SetMaskScreenGraph(gh);
+        SetMaskScreenGraphUseChannel(ch);
+      }
+      SetUseMaskScreenFlag(TRUE);
+    }
+    MaskScope{reverse, g, prev}
+  }
+}
+
+/// Drop for MaskScope
+impl Drop for MaskScope {
+  /// restore the settings before new
+  fn drop(&mut self) {
+    let (u, r, g) = self.prev;
+    unsafe {
+      SetUseMaskScreenFlag(u);
+      SetMaskReverseEffectFlag(r);
+      if self.g.is_some() {
+        let (gh, ch) = g.unwrap_or((-1, DX_MASKGRAPH_CH_A)); // -1: release
+        SetMaskScreenGraph(gh);
+        SetMaskScreenGraphUseChannel(ch);
+      }
+    }
+  }
+}
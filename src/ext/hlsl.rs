@@ -0,0 +1,598 @@
+//! hlsl ext dx bridge for DxLib
+//!
+//! cbuffer and struct declarations of HLSL source with the packing layout
+//! - #include is resolved, #define of a single value is expanded
+//! - other preprocessor lines (#if etc) and packoffset are not supported
+//! - a struct starts on a register, members after it may pack in its tail
+//!
+//! ```ignore
+//! let h = Hlsl::load("./resource/shader_PS.hlsl", &["./resource"])?;
+//! let cb = h.cbuffer("cb_CamLight").ok_or("no cbuffer")?;
+//! cb.check(&cb8)?; // slot and number of FLOAT4
+//! h.verify::<CamLight>("cb_CamLight")?; // offsets of the members
+//! println!("{}", h.to_rust("cb_CamLight")?);
+//! ```
+//!
+
+use std::error::Error;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::ext::cbuffer::{CbLayout, REGISTER};
+use crate::ext::shader::ConstantBuffer;
+
+/// Member (offset and size in bytes)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+  /// name
+  pub name: String,
+  /// type (float3 float4x4 struct name etc)
+  pub ty: String,
+  /// array dimensions (empty: not an array)
+  pub dims: Vec<usize>,
+  /// row_major
+  pub row_major: bool,
+  /// offset from the top of the cbuffer or struct
+  pub offset: usize,
+  /// size (the last element is not padded)
+  pub size: usize,
+  /// distance between elements (0: not an array)
+  pub stride: usize
+}
+
+/// Member
+impl Member {
+  /// number of elements (0: not an array)
+  pub fn count(&self) -> usize {
+    if self.dims.is_empty() { 0 } else { self.dims.iter().product() }
+  }
+  /// size of an element (size when not an array)
+  pub fn elem_size(&self) -> usize {
+    match self.count() {
+    0 => self.size,
+    c => self.size - self.stride * (c - 1)
+    }
+  }
+}
+
+/// CBuffer (cbuffer or struct)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CBuffer {
+  /// name
+  pub name: String,
+  /// register(bN) (None: struct or no register)
+  pub slot: Option<i32>,
+  /// members
+  pub members: Vec<Member>,
+  /// size (cbuffer: multiple of 16, struct: end of the last member)
+  pub size: usize
+}
+
+/// CBuffer
+impl CBuffer {
+  /// member by name
+  pub fn member(&self, n: &str) -> Option<&Member> {
+    self.members.iter().find(|m| m.name == n)
+  }
+  /// number of FLOAT4 (n of create_constant_buffer)
+  pub fn n(&self) -> i32 { self.size.div_ceil(REGISTER) as i32 }
+  /// check slot and number of FLOAT4 of the created buffer
+  pub fn check<T>(&self, cb: &ConstantBuffer<T>)
+    -> Result<(), Box<dyn Error>> {
+    if let Some(s) = self.slot {
+      if s != cb.s {
+        return Err(format!("{}: slot b{} but buffer has {}",
+          self.name, s, cb.s).into())
+      }
+    }
+    if cb.n != self.n() {
+      return Err(format!("{}: {} FLOAT4 but buffer has {}",
+        self.name, self.n(), cb.n).into())
+    }
+    Ok(())
+  }
+}
+
+/// Hlsl (declarations)
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Hlsl {
+  /// structs
+  pub structs: Vec<CBuffer>,
+  /// cbuffers
+  pub cbuffers: Vec<CBuffer>
+}
+
+/// Hlsl
+impl Hlsl {
+  /// load file (#include from the directory of the file and dirs)
+  pub fn load(n: &str, dirs: &[&str]) -> Result<Hlsl, Box<dyn Error>> {
+    let mut defs = HashMap::new();
+    let dirs: Vec<PathBuf> = dirs.iter().map(PathBuf::from).collect();
+    let s = preprocess(Path::new(n.trim_end_matches('\0')), &dirs,
+      &mut defs, 0)?;
+    Hlsl::parse_tokens(tokenize(&s), &defs)
+  }
+  /// parse source (no #include)
+  pub fn parse(s: &str) -> Result<Hlsl, Box<dyn Error>> {
+    let mut defs = HashMap::new();
+    let s = directives(&strip_comments(s), &mut defs, &mut |l, _| {
+      Err(format!("hlsl: #include {} without file", l).into())
+    })?;
+    Hlsl::parse_tokens(tokenize(&s), &defs)
+  }
+  /// struct by name
+  pub fn struct_of(&self, n: &str) -> Option<&CBuffer> {
+    self.structs.iter().find(|s| s.name == n)
+  }
+  /// cbuffer by name
+  pub fn cbuffer(&self, n: &str) -> Option<&CBuffer> {
+    self.cbuffers.iter().find(|c| c.name == n)
+  }
+  /// cbuffer by register(bN)
+  pub fn cbuffer_at(&self, slot: i32) -> Option<&CBuffer> {
+    self.cbuffers.iter().find(|c| c.slot == Some(slot))
+  }
+  /// check the layout of T (declared by cbuffer!) against the cbuffer
+  /// - a single struct member (CamLight g_CL) may be matched by its members
+  /// - fields of T beginning with '_' are padding
+  pub fn verify<T: CbLayout>(&self, n: &str) -> Result<(), Box<dyn Error>> {
+    let cb = self.cbuffer(n).ok_or_else(|| format!("hlsl: no cbuffer {}", n))?;
+    let sz = std::mem::size_of::<T>();
+    if sz != cb.n() as usize * REGISTER {
+      return Err(format!("{}: size {} but Rust has {}",
+        n, cb.n() as usize * REGISTER, sz).into())
+    }
+    let has = |f: &str| T::FIELDS.iter().any(|(k, _, _)| *k == f);
+    let ms = match &cb.members[..] {
+    [m] if m.dims.is_empty() && !has(&m.name) => match self.struct_of(&m.ty) {
+      Some(s) => &s.members,
+      None => &cb.members
+      },
+    _ => &cb.members
+    };
+    for m in ms.iter() {
+      let (_, o, s) = T::FIELDS.iter().find(|(k, _, _)| *k == m.name)
+        .ok_or_else(|| format!("{}: {} is not in Rust", n, m.name))?;
+      let e = self.rust_size(m);
+      if (*o, *s) != (m.offset, e) {
+        return Err(format!("{}.{}: offset {} size {} but Rust has {} {}",
+          n, m.name, m.offset, e, o, s).into())
+      }
+    }
+    for (k, _, _) in T::FIELDS.iter() {
+      if !k.starts_with('_') && !ms.iter().any(|m| m.name == *k) {
+        return Err(format!("{}: Rust {} is not in HLSL", n, k).into())
+      }
+    }
+    Ok(())
+  }
+  /// size of the member in Rust (structs and elements padded to 16)
+  fn rust_size(&self, m: &Member) -> usize {
+    let c = m.count();
+    if c > 0 { m.stride * c }
+    else if self.struct_of(&m.ty).is_some() { round(m.size) }
+    else { m.size }
+  }
+  /// generate Rust source of the cbuffer (and its structs) by cbuffer!
+  pub fn to_rust(&self, n: &str) -> Result<String, Box<dyn Error>> {
+    let cb = self.cbuffer(n).ok_or_else(|| format!("hlsl: no cbuffer {}", n))?;
+    let mut done = vec![];
+    let mut o = String::new();
+    self.gen(cb, &mut done, &mut o)?;
+    Ok(o)
+  }
+  /// generate structs used by b then b
+  fn gen(&self, b: &CBuffer, done: &mut Vec<String>, o: &mut String)
+    -> Result<(), Box<dyn Error>> {
+    let mut fs = vec![];
+    let (mut pos, mut pad) = (0, 0);
+    for m in b.members.iter() {
+      if let Some(s) = self.struct_of(&m.ty) {
+        if !done.contains(&s.name) {
+          done.push(s.name.clone());
+          self.gen(s, done, o)?;
+        }
+      }
+      if m.offset < pos {
+        return Err(format!("{}.{}: packed into the padding of Rust",
+          b.name, m.name).into())
+      }
+      while pos < m.offset {
+        fs.push(format!("pub _pad{}: f32", pad));
+        (pos, pad) = (pos + 4, pad + 1);
+      }
+      let mut t = self.rust_type(m)?;
+      for d in m.dims.iter().rev() { t = format!("[{}; {}]", t, d); }
+      fs.push(format!("pub {}: {}", m.name, t));
+      pos += self.rust_size(m);
+    }
+    while pos % REGISTER != 0 {
+      fs.push(format!("pub _pad{}: f32", pad));
+      (pos, pad) = (pos + 4, pad + 1);
+    }
+    o.push_str(&format!("dxlib::cbuffer! {{\n  /// {}{}\n", b.name,
+      b.slot.map(|s| format!(" (register b{})", s)).unwrap_or_default()));
+    o.push_str("  #[allow(non_camel_case_types, non_snake_case)]\n");
+    o.push_str(&format!("  pub struct {} {{\n    {}\n  }}\n}}\n",
+      b.name, fs.join(",\n    ")));
+    Ok(())
+  }
+  /// Rust type of the member (element)
+  fn rust_type(&self, m: &Member) -> Result<String, Box<dyn Error>> {
+    let t = match m.ty.as_str() {
+    "float" | "float1" => "f32",
+    "int" | "int1" => "i32",
+    "uint" | "uint1" | "dword" | "bool" => "u32",
+    "float2" => "FLOAT2",
+    "float3" => "VECTOR",
+    "float4" => "FLOAT4",
+    "float4x4" | "matrix" => "MATRIX",
+    t if self.struct_of(t).is_some() => t,
+    t => return Err(format!("{}: no Rust type for {}", m.name, t).into())
+    };
+    let e = Member{dims: vec![], size: m.elem_size(), ..m.clone()};
+    if !m.dims.is_empty() && m.stride != self.rust_size(&e) {
+      return Err(format!("{}: elements of {} are padded (use float4)",
+        m.name, t).into())
+    }
+    Ok(t.to_string())
+  }
+  /// parse declarations
+  fn parse_tokens(ts: Vec<String>, defs: &HashMap<String, String>)
+    -> Result<Hlsl, Box<dyn Error>> {
+    let mut h = Hlsl::default();
+    let mut p = Tokens{ts, i: 0, defs, depth: 0};
+    while let Some(t) = p.next() {
+      match t.as_str() {
+      "struct" => {
+        let n = p.ident()?;
+        if p.peek() != Some("{") { continue } // struct S s; etc
+        let b = p.block(&n, None, &h)?;
+        h.structs.push(b);
+      },
+      "cbuffer" => {
+        let n = p.ident()?;
+        let mut slot = None;
+        if p.peek() == Some(":") {
+          p.next();
+          p.expect("register")?;
+          p.expect("(")?;
+          let r = p.ident()?;
+          slot = Some(r.strip_prefix(['b', 'B']).and_then(|s| s.parse().ok())
+            .ok_or_else(|| format!("hlsl: cbuffer {} register {}", n, r))?);
+          p.expect(")")?;
+        }
+        let mut b = p.block(&n, slot, &h)?;
+        b.size = round(b.size);
+        h.cbuffers.push(b);
+      },
+      "{" => p.skip_block()?,
+      _ => ()
+      }
+    }
+    Ok(h)
+  }
+}
+
+/// round up to the register
+fn round(n: usize) -> usize { n.div_ceil(REGISTER) * REGISTER }
+
+/// round up to the register (None: overflow)
+fn round_checked(n: usize) -> Option<usize> {
+  n.div_ceil(REGISTER).checked_mul(REGISTER)
+}
+
+/// size of the type and starts on a register (column_major by default)
+fn type_size(t: &str, row_major: bool, h: &Hlsl)
+  -> Result<(usize, bool), Box<dyn Error>> {
+  if let Some(s) = h.struct_of(t) { return Ok((s.size, true)) }
+  let t = if t == "matrix" { "float4x4" } else if t == "vector" { "float4" }
+    else { t };
+  let b = t.trim_end_matches(|c: char| c.is_ascii_digit() || c == 'x');
+  let e = match b {
+  "float" | "int" | "uint" | "bool" | "dword" | "half" => 4,
+  "min16float" | "min10float" | "min16int" | "min12int" | "min16uint" => 4,
+  "double" => 8,
+  _ => return Err(format!("hlsl: unknown type {}", t).into())
+  };
+  let d: Vec<usize> = t[b.len()..].split('x').filter(|s| !s.is_empty())
+    .map(|s| s.parse()).collect::<Result<_, _>>()?;
+  match d[..] {
+  [] => Ok((e, false)),
+  [n] if (1..=4).contains(&n) => Ok((e * n, false)),
+  [r, c] if (1..=4).contains(&r) && (1..=4).contains(&c) => {
+    let (regs, comps) = if row_major { (r, c) } else { (c, r) };
+    Ok(((regs - 1) * REGISTER + comps * e, true))
+  },
+  _ => Err(format!("hlsl: bad type {}", t).into())
+  }
+}
+
+/// strip comments
+fn strip_comments(s: &str) -> String {
+  let mut o = String::with_capacity(s.len());
+  let mut r = s;
+  while let Some(p) = r.find('/') {
+    o.push_str(&r[..p]);
+    let t = &r[p..];
+    if t.starts_with("//") {
+      r = &t[t.find('\n').unwrap_or(t.len())..];
+    } else if let Some(b) = t.strip_prefix("/*") {
+      let e = b.find("*/").map(|e| e + 2).unwrap_or(b.len());
+      o.push_str(&"\n".repeat(b[..e].matches('\n').count())); // keep lines
+      r = &b[e..];
+    } else {
+      o.push('/');
+      r = &t[1..];
+    }
+  }
+  o.push_str(r);
+  o
+}
+
+/// Include (returns the source of the included file)
+type Include<'a> = dyn FnMut(&str, &mut HashMap<String, String>)
+  -> Result<String, Box<dyn Error>> + 'a;
+
+/// apply #define and #include
+fn directives(s: &str, defs: &mut HashMap<String, String>,
+  inc: &mut Include) -> Result<String, Box<dyn Error>> {
+  let mut o = String::with_capacity(s.len());
+  let s = s.replace("\\\r\n", " ").replace("\\\n", " ");
+  for l in s.lines() {
+    let Some(d) = l.trim_start().strip_prefix('#') else {
+      o.push_str(l);
+      o.push('\n');
+      continue
+    };
+    let d = d.trim_start();
+    if let Some(f) = d.strip_prefix("include") {
+      o.push_str(&inc(f.trim(), defs)?);
+      o.push('\n');
+    } else if let Some(r) = d.strip_prefix("define") {
+      let mut w = r.split_whitespace();
+      if let Some(k) = w.next() {
+        if !k.contains('(') {
+          defs.insert(k.to_string(), w.collect::<Vec<_>>().join(" "));
+        }
+      }
+    }
+  }
+  Ok(o)
+}
+
+/// read file and resolve #include recursively
+fn preprocess(f: &Path, dirs: &[PathBuf], defs: &mut HashMap<String, String>,
+  depth: usize) -> Result<String, Box<dyn Error>> {
+  if depth > 32 { return Err(format!("hlsl: include depth {:?}", f).into()) }
+  let s = std::fs::read_to_string(f)
+    .map_err(|e| format!("hlsl: {:?}: {}", f, e))?;
+  let here = f.parent().map(Path::to_path_buf).unwrap_or_default();
+  directives(&strip_comments(&s), defs, &mut |l, defs| {
+    let n = l.trim_matches(|c| c == '<' || c == '>' || c == '"');
+    let p = std::iter::once(&here).chain(dirs.iter()).map(|d| d.join(n))
+      .find(|p| p.is_file())
+      .ok_or_else(|| format!("hlsl: {:?}: #include {} not found", f, l))?;
+    preprocess(&p, dirs, defs, depth + 1)
+  })
+}
+
+/// split to tokens (identifiers numbers and punctuations)
+fn tokenize(s: &str) -> Vec<String> {
+  let mut ts = vec![];
+  let cs: Vec<char> = s.chars().collect();
+  let mut i = 0;
+  while i < cs.len() {
+    let c = cs[i];
+    if c.is_whitespace() { i += 1; continue }
+    let j = if c.is_alphanumeric() || c == '_' || c == '.' {
+      let mut j = i;
+      while j < cs.len() && (cs[j].is_alphanumeric() || "_.".contains(cs[j])) {
+        j += 1;
+      }
+      j
+    } else if c == '"' {
+      let mut j = i + 1;
+      while j < cs.len() && cs[j] != '"' { j += 1; }
+      (j + 1).min(cs.len())
+    } else { i + 1 };
+    ts.push(cs[i..j].iter().collect());
+    i = j;
+  }
+  ts
+}
+
+/// Tokens
+struct Tokens<'a> {
+  /// tokens
+  ts: Vec<String>,
+  /// position
+  i: usize,
+  /// #define
+  defs: &'a HashMap<String, String>,
+  /// depth of #define expansion
+  depth: usize
+}
+
+/// Tokens
+impl Tokens<'_> {
+  /// peek
+  fn peek(&self) -> Option<&str> { self.ts.get(self.i).map(|t| t.as_str()) }
+  /// next
+  fn next(&mut self) -> Option<String> {
+    let t = self.ts.get(self.i).cloned();
+    self.i += 1;
+    t
+  }
+  /// expect t
+  fn expect(&mut self, t: &str) -> Result<(), Box<dyn Error>> {
+    match self.next() {
+    Some(n) if n == t => Ok(()),
+    n => Err(format!("hlsl: expected {} but {:?}", t, n).into())
+    }
+  }
+  /// identifier
+  fn ident(&mut self) -> Result<String, Box<dyn Error>> {
+    match self.next() {
+    Some(n) if n.starts_with(|c: char| c.is_alphabetic() || c == '_') => Ok(n),
+    n => Err(format!("hlsl: expected identifier but {:?}", n).into())
+    }
+  }
+  /// skip { ... } (after {)
+  fn skip_block(&mut self) -> Result<(), Box<dyn Error>> {
+    let mut d = 1;
+    while d > 0 {
+      match self.next().ok_or("hlsl: unterminated block")?.as_str() {
+      "{" => d += 1,
+      "}" => d -= 1,
+      _ => ()
+      }
+    }
+    Ok(())
+  }
+  /// skip initializer to , or ; (not consumed)
+  fn skip_init(&mut self) -> Result<(), Box<dyn Error>> {
+    let mut d = 0;
+    loop {
+      match self.peek().ok_or("hlsl: unterminated initializer")? {
+      "(" | "{" => d += 1,
+      ")" | "}" => d -= 1,
+      "," | ";" if d == 0 => return Ok(()),
+      _ => ()
+      }
+      self.i += 1;
+    }
+  }
+  /// constant expression of array size (+ - * / % and parentheses)
+  fn expr(&mut self) -> Result<i64, Box<dyn Error>> {
+    let mut v = self.term()?;
+    while let Some(o) = self.peek().filter(|o| *o == "+" || *o == "-") {
+      let o = o == "+";
+      self.i += 1;
+      let t = self.term()?;
+      v = if o { v.checked_add(t) } else { v.checked_sub(t) }
+        .ok_or("hlsl: overflow in expression")?;
+    }
+    Ok(v)
+  }
+  /// term
+  fn term(&mut self) -> Result<i64, Box<dyn Error>> {
+    let mut v = self.factor()?;
+    while let Some(o) = self.peek().filter(|o| ["*", "/", "%"].contains(o)) {
+      let o = o.to_string();
+      self.i += 1;
+      let f = self.factor()?;
+      if o != "*" && f == 0 { return Err("hlsl: division by 0".into()) }
+      v = match o.as_str() {
+      "*" => v.checked_mul(f),
+      "/" => v.checked_div(f),
+      _ => v.checked_rem(f)
+      }.ok_or("hlsl: overflow in expression")?;
+    }
+    Ok(v)
+  }
+  /// factor
+  fn factor(&mut self) -> Result<i64, Box<dyn Error>> {
+    let t = self.next().ok_or("hlsl: unterminated expression")?;
+    match t.as_str() {
+    "(" => { let v = self.expr()?; self.expect(")")?; Ok(v) },
+    "-" => self.factor()?.checked_neg()
+      .ok_or_else(|| "hlsl: overflow in expression".into()),
+    t if self.defs.contains_key(t) => {
+      if self.depth > 16 { return Err(format!("hlsl: #define {}", t).into()) }
+      let mut d = Tokens{ts: tokenize(&self.defs[t]), i: 0, defs: self.defs,
+        depth: self.depth + 1};
+      let v = d.expr()?;
+      if d.i != d.ts.len() { return Err(format!("hlsl: #define {}", t).into()) }
+      Ok(v)
+    },
+    t => {
+      let t = t.trim_end_matches(['u', 'U', 'l', 'L']);
+      let v = if let Some(x) = t.strip_prefix("0x") {
+        i64::from_str_radix(x, 16).ok()
+      } else { t.parse().ok() };
+      v.ok_or_else(|| format!("hlsl: not a constant {}", t).into())
+    }
+    }
+  }
+  /// members { ... } (packed from offset 0)
+  fn block(&mut self, n: &str, slot: Option<i32>, h: &Hlsl)
+    -> Result<CBuffer, Box<dyn Error>> {
+    self.expect("{")?;
+    let mut b = CBuffer{name: n.to_string(), slot, members: vec![], size: 0};
+    while self.peek() != Some("}") {
+      let mut row_major = false;
+      let mut skip = false;
+      let mut ty = self.ident()?;
+      while let Some(m) = ["row_major", "column_major", "precise", "static",
+        "const", "uniform", "linear", "nointerpolation", "shared", "volatile"]
+        .iter().find(|m| **m == ty) {
+        row_major = *m == "row_major" || (row_major && *m != "column_major");
+        skip |= *m == "static";
+        ty = self.ident()?;
+      }
+      if self.peek() == Some("<") { // vector<float, 4> matrix<float, 4, 4>
+        self.next();
+        let e = self.ident()?;
+        let mut d = vec![];
+        while self.peek() == Some(",") {
+          self.next();
+          d.push(self.expr()?.to_string());
+        }
+        self.expect(">")?;
+        ty = format!("{}{}", e, d.join("x"));
+      }
+      loop {
+        let name = self.ident()?;
+        let mut dims = vec![];
+        while self.peek() == Some("[") {
+          self.next();
+          let d = self.expr()?;
+          if d <= 0 { return Err(format!("hlsl: {}[{}]", name, d).into()) }
+          dims.push(d as usize);
+          self.expect("]")?;
+        }
+        if self.peek() == Some(":") {
+          self.next();
+          let s = self.ident()?;
+          if s == "packoffset" {
+            return Err(format!("hlsl: {}: packoffset", name).into())
+          }
+          if self.peek() == Some("(") { self.skip_init()?; } // register(c0)
+        }
+        if self.peek() == Some("=") { self.skip_init()?; }
+        if !skip {
+          let (sz, reg) = type_size(&ty, row_major, h)
+            .map_err(|e| format!("{}.{}: {}", n, name, e))?;
+          let m = place(&b, name, &ty, dims, row_major, sz, reg)
+            .ok_or_else(|| format!("hlsl: {}: too large", n))?;
+          b.size = m.offset.checked_add(m.size)
+            .ok_or_else(|| format!("hlsl: {}: too large", n))?;
+          b.members.push(m);
+        }
+        match self.next().as_deref() {
+        Some(",") => continue,
+        Some(";") => break,
+        t => return Err(format!("hlsl: {}: unexpected {:?}", n, t).into())
+        }
+      }
+    }
+    self.expect("}")?;
+    if self.peek() == Some(";") { self.next(); }
+    Ok(b)
+  }
+}
+
+/// place the member after the last member of b (None: overflow)
+fn place(b: &CBuffer, name: String, ty: &str, dims: Vec<usize>,
+  row_major: bool, sz: usize, reg: bool) -> Option<Member> {
+  let o = b.size;
+  let c = dims.iter().try_fold(1usize, |c, d| c.checked_mul(*d))?;
+  let (offset, size, stride) = if !dims.is_empty() {
+    let st = round_checked(sz)?;
+    (round_checked(o)?, st.checked_mul(c - 1)?.checked_add(sz)?, st)
+  } else if reg || o % REGISTER + sz > REGISTER {
+    (round_checked(o)?, sz, 0)
+  } else { (o, sz, 0) };
+  Some(Member{name, ty: ty.to_string(), dims, row_major, offset, size, stride})
+}
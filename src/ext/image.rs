@@ -0,0 +1,185 @@
+//! image ext dx bridge for DxLib
+//!
+//! image header inspection without DxLib (PNG BMP JPEG)
+//!
+
+use std::error::Error;
+use std::io::Read;
+
+/// ImageKind
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageKind {
+  /// png
+  Png,
+  /// bmp
+  Bmp,
+  /// jpeg
+  Jpeg
+}
+
+/// ImageInfo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageInfo {
+  /// kind
+  pub kind: ImageKind,
+  /// w
+  pub w: i32,
+  /// h
+  pub h: i32
+}
+
+/// ImageKind
+impl ImageKind {
+  /// kind by the signature (None: unknown format)
+  pub fn detect(b: &[u8]) -> Option<Self> {
+    if b.starts_with(b"\x89PNG\r\n\x1a\n") { Some(ImageKind::Png) }
+    else if b.starts_with(b"BM") { Some(ImageKind::Bmp) }
+    else if b.starts_with(&[0xff, 0xd8]) { Some(ImageKind::Jpeg) }
+    else { None }
+  }
+}
+
+/// ImageInfo
+impl ImageInfo {
+  /// read the header of the file
+  pub fn load(n: &str) -> Result<Self, Box<dyn Error>> {
+    let n = n.trim_end_matches('\0');
+    ImageInfo::from_bytes(&head(n)?)
+      .map_err(|e| format!("{}: {}", n, e).into())
+  }
+  /// read the header of the file
+  /// - None: unknown format or not readable here (left to DxLib, DXA etc)
+  /// - Err: broken header of the known format
+  pub fn probe(n: &str) -> Result<Option<Self>, Box<dyn Error>> {
+    let n = n.trim_end_matches('\0');
+    match head(n) {
+    Ok(b) if ImageKind::detect(&b).is_some() => ImageInfo::from_bytes(&b)
+      .map(Some).map_err(|e| format!("{}: {}", n, e).into()),
+    _ => Ok(None)
+    }
+  }
+  /// parse the header
+  pub fn from_bytes(b: &[u8]) -> Result<Self, Box<dyn Error>> {
+    let b4 = |i: usize| [b[i], b[i + 1], b[i + 2], b[i + 3]];
+    let be16 = |i: usize| u16::from_be_bytes([b[i], b[i + 1]]) as i32;
+    let le32 = |i: usize| i32::from_le_bytes(b4(i));
+    match ImageKind::detect(b) {
+    Some(ImageKind::Png) => {
+      if b.len() < 24 || &b[12..16] != b"IHDR" {
+        return Err("png: no IHDR".into())
+      }
+      let be32 = |i: usize| i32::try_from(u32::from_be_bytes(b4(i)));
+      match (be32(16), be32(20)) {
+      (Ok(w), Ok(h)) => Ok(ImageInfo{kind: ImageKind::Png, w, h}),
+      _ => Err("png: bad size".into())
+      }
+    },
+    Some(ImageKind::Bmp) => {
+      if b.len() < 26 { return Err("bmp: short header".into()) }
+      let (w, h) = if le32(14) == 12 { // OS/2 BITMAPCOREHEADER
+        (u16::from_le_bytes([b[18], b[19]]) as i32,
+         u16::from_le_bytes([b[20], b[21]]) as i32)
+      } else { (le32(18), le32(22).checked_abs() // negative: top down
+        .ok_or("bmp: bad height")?) };
+      if w < 0 { return Err("bmp: bad width".into()) }
+      Ok(ImageInfo{kind: ImageKind::Bmp, w, h})
+    },
+    Some(ImageKind::Jpeg) => {
+      let mut i = 2;
+      while i + 4 <= b.len() {
+        if b[i] != 0xff { return Err("jpeg: bad marker".into()) }
+        let m = b[i + 1];
+        if m == 0xff { i += 1; continue } // fill
+        if m == 0xd8 || m == 0x01 || (0xd0..=0xd7).contains(&m) {
+          i += 2;
+          continue
+        }
+        if m == 0xda { break } // scan data before SOF
+        let l = be16(i + 2) as usize;
+        if matches!(m, 0xc0..=0xcf) && !matches!(m, 0xc4 | 0xc8 | 0xcc) {
+          if i + 9 > b.len() { break }
+          return Ok(ImageInfo{kind: ImageKind::Jpeg,
+            w: be16(i + 7), h: be16(i + 5)})
+        }
+        i += 2 + l;
+      }
+      Err("jpeg: no SOF".into())
+    },
+    None => Err("unknown image format".into())
+    }
+  }
+}
+
+/// head of the file (enough for JPEG SOF)
+fn head(n: &str) -> std::io::Result<Vec<u8>> {
+  let mut b = vec![];
+  std::fs::File::open(n)?.take(1 << 20).read_to_end(&mut b)?;
+  Ok(b)
+}
+
+/// DivGrid (parameters of LoadDivGraph)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DivGrid {
+  /// number of cells
+  pub allnum: i32,
+  /// columns
+  pub xnum: i32,
+  /// rows
+  pub ynum: i32,
+  /// cell w
+  pub xsz: i32,
+  /// cell h
+  pub ysz: i32,
+  /// distance between left of cells (0: xsz)
+  pub xstride: i32,
+  /// distance between top of cells (0: ysz)
+  pub ystride: i32
+}
+
+/// DivGrid
+impl DivGrid {
+  /// all cells of w h image (no gap)
+  pub fn auto(w: i32, h: i32, xsz: i32, ysz: i32)
+    -> Result<Self, Box<dyn Error>> {
+    if xsz <= 0 || ysz <= 0 { return Err("div grid: cell size".into()) }
+    let (xnum, ynum) = (w / xsz, h / ysz);
+    let allnum = xnum.checked_mul(ynum).ok_or("div grid: too many cells")?;
+    let g = DivGrid{allnum, xnum, ynum, xsz, ysz, xstride: 0, ystride: 0};
+    g.check(w, h)?;
+    Ok(g)
+  }
+  /// check count stride and bounds on w h image
+  pub fn check(&self, w: i32, h: i32) -> Result<(), Box<dyn Error>> {
+    let (sx, sy) = (if self.xstride == 0 { self.xsz } else { self.xstride },
+      if self.ystride == 0 { self.ysz } else { self.ystride });
+    if self.xsz <= 0 || self.ysz <= 0 {
+      return Err(format!("div grid: cell {}x{}", self.xsz, self.ysz).into())
+    }
+    if self.xnum <= 0 || self.ynum <= 0 || self.allnum <= 0 {
+      return Err(format!("div grid: count {} ({}x{})",
+        self.allnum, self.xnum, self.ynum).into())
+    }
+    if self.allnum as i64 > self.xnum as i64 * self.ynum as i64 {
+      return Err(format!("div grid: count {} > {}x{}",
+        self.allnum, self.xnum, self.ynum).into())
+    }
+    if self.xstride < 0 || self.ystride < 0 {
+      return Err(format!("div grid: stride {}x{}",
+        self.xstride, self.ystride).into())
+    }
+    let (cols, rows) = (self.xnum.min(self.allnum),
+      (self.allnum - 1) / self.xnum + 1); // cells in use
+    let span = |n: i32, s: i32, sz: i32|
+      (n - 1).checked_mul(s).and_then(|v| v.checked_add(sz));
+    let (cw, ch) = match (span(cols, sx, self.xsz), span(rows, sy, self.ysz)) {
+    (Some(cw), Some(ch)) => (cw, ch),
+    _ => return Err(format!("div grid: {}x{} cells of stride {}x{} overflow",
+      cols, rows, sx, sy).into())
+    };
+    if cw > w || ch > h {
+      return Err(format!("div grid: {}x{} out of image {}x{}",
+        cw, ch, w, h).into())
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,195 @@
+//! json ext dx bridge for DxLib
+//!
+//! minimum JSON reader for resource descriptors (atlas, map etc)
+//!
+
+use std::error::Error;
+
+/// Json
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+  /// null
+  Null,
+  /// true false
+  Bool(bool),
+  /// number
+  Num(f64),
+  /// string
+  Str(String),
+  /// array
+  Arr(Vec<Json>),
+  /// object (keep order)
+  Obj(Vec<(String, Json)>)
+}
+
+/// Json
+impl Json {
+  /// parse
+  pub fn parse(s: &str) -> Result<Json, Box<dyn Error>> {
+    let mut p = Parser{b: s.as_bytes(), i: 0};
+    let v = p.value()?;
+    p.ws();
+    if p.i != p.b.len() { return Err(p.err("trailing characters")) }
+    Ok(v)
+  }
+  /// get member of object
+  pub fn get(&self, k: &str) -> Option<&Json> {
+    match self {
+    Json::Obj(o) => o.iter().find(|(n, _)| n == k).map(|(_, v)| v),
+    _ => None
+    }
+  }
+  /// as bool
+  pub fn as_bool(&self) -> Option<bool> {
+    match self { Json::Bool(b) => Some(*b), _ => None }
+  }
+  /// as f64
+  pub fn as_f64(&self) -> Option<f64> {
+    match self { Json::Num(n) => Some(*n), _ => None }
+  }
+  /// as i32
+  pub fn as_i32(&self) -> Option<i32> { self.as_f64().map(|n| n as i32) }
+  /// as str
+  pub fn as_str(&self) -> Option<&str> {
+    match self { Json::Str(s) => Some(s), _ => None }
+  }
+  /// as array
+  pub fn as_arr(&self) -> Option<&Vec<Json>> {
+    match self { Json::Arr(a) => Some(a), _ => None }
+  }
+  /// as object
+  pub fn as_obj(&self) -> Option<&Vec<(String, Json)>> {
+    match self { Json::Obj(o) => Some(o), _ => None }
+  }
+}
+
+/// Parser
+struct Parser<'a> {
+  /// source
+  b: &'a [u8],
+  /// position
+  i: usize
+}
+
+/// Parser
+impl Parser<'_> {
+  /// error with position
+  fn err(&self, m: &str) -> Box<dyn Error> {
+    format!("json: {} at {}", m, self.i).into()
+  }
+  /// skip white spaces
+  fn ws(&mut self) {
+    while self.i < self.b.len() && self.b[self.i].is_ascii_whitespace() {
+      self.i += 1;
+    }
+  }
+  /// peek
+  fn peek(&self) -> Option<u8> { self.b.get(self.i).copied() }
+  /// expect
+  fn expect(&mut self, c: u8) -> Result<(), Box<dyn Error>> {
+    self.ws();
+    if self.peek() != Some(c) {
+      return Err(self.err(&format!("expected '{}'", c as char)))
+    }
+    self.i += 1;
+    Ok(())
+  }
+  /// literal
+  fn lit(&mut self, l: &str, v: Json) -> Result<Json, Box<dyn Error>> {
+    if !self.b[self.i..].starts_with(l.as_bytes()) {
+      return Err(self.err("unknown literal"))
+    }
+    self.i += l.len();
+    Ok(v)
+  }
+  /// value
+  fn value(&mut self) -> Result<Json, Box<dyn Error>> {
+    self.ws();
+    match self.peek() {
+    None => Err(self.err("unexpected end")),
+    Some(b'{') => {
+      self.i += 1;
+      let mut o = vec![];
+      self.ws();
+      if self.peek() == Some(b'}') { self.i += 1; return Ok(Json::Obj(o)) }
+      loop {
+        self.ws();
+        let k = self.string()?;
+        self.expect(b':')?;
+        o.push((k, self.value()?));
+        self.ws();
+        match self.peek() {
+        Some(b',') => self.i += 1,
+        Some(b'}') => { self.i += 1; return Ok(Json::Obj(o)) },
+        _ => return Err(self.err("expected ',' or '}'"))
+        }
+      }
+    },
+    Some(b'[') => {
+      self.i += 1;
+      let mut a = vec![];
+      self.ws();
+      if self.peek() == Some(b']') { self.i += 1; return Ok(Json::Arr(a)) }
+      loop {
+        a.push(self.value()?);
+        self.ws();
+        match self.peek() {
+        Some(b',') => self.i += 1,
+        Some(b']') => { self.i += 1; return Ok(Json::Arr(a)) },
+        _ => return Err(self.err("expected ',' or ']'"))
+        }
+      }
+    },
+    Some(b'"') => Ok(Json::Str(self.string()?)),
+    Some(b't') => self.lit("true", Json::Bool(true)),
+    Some(b'f') => self.lit("false", Json::Bool(false)),
+    Some(b'n') => self.lit("null", Json::Null),
+    Some(_) => self.number()
+    }
+  }
+  /// number
+  fn number(&mut self) -> Result<Json, Box<dyn Error>> {
+    let s = self.i;
+    while self.i < self.b.len() && matches!(self.b[self.i],
+      b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') {
+      self.i += 1;
+    }
+    let t = std::str::from_utf8(&self.b[s..self.i])?;
+    t.parse::<f64>().map(Json::Num).map_err(|_| self.err("bad number"))
+  }
+  /// string
+  fn string(&mut self) -> Result<String, Box<dyn Error>> {
+    if self.peek() != Some(b'"') { return Err(self.err("expected string")) }
+    self.i += 1;
+    let mut v: Vec<u8> = vec![];
+    loop {
+      match self.peek() {
+      None => return Err(self.err("unterminated string")),
+      Some(b'"') => { self.i += 1; break },
+      Some(b'\\') => {
+        self.i += 1;
+        let c = self.peek().ok_or_else(|| self.err("bad escape"))?;
+        self.i += 1;
+        match c {
+        b'n' => v.push(b'\n'),
+        b't' => v.push(b'\t'),
+        b'r' => v.push(b'\r'),
+        b'b' => v.push(0x08),
+        b'f' => v.push(0x0c),
+        b'u' => {
+          let h = self.b.get(self.i..self.i + 4)
+            .ok_or_else(|| self.err("bad unicode escape"))?;
+          let u = u32::from_str_radix(std::str::from_utf8(h)?, 16)?;
+          self.i += 4;
+          let ch = char::from_u32(u).unwrap_or('\u{fffd}');
+          v.extend_from_slice(ch.to_string().as_bytes());
+        },
+        _ => v.push(c) // '"' '\\' '/'
+        }
+      },
+      Some(c) => { v.push(c); self.i += 1; }
+      }
+    }
+    Ok(String::from_utf8(v)?)
+  }
+}
@@ -1,11 +1,16 @@
 //! light ext dx bridge for DxLib
 //!
 
+use std::error::Error;
+
 use crate::{dx::*, ext::tdx::*};
 
 /// LightParamSub (see also dx::LIGHTPARAM)
+/// - range attenuation are for point and spot, theta phi for spot
+/// - DxLib does not set fall_off (always 1.0)
+#[derive(Debug, Clone)]
 pub struct LightParamSub {
-  /// light type (TODO: now only DX_LIGHTTYPE_DIRECTIONAL)
+  /// DX_LIGHTTYPE_DIRECTIONAL DX_LIGHTTYPE_POINT DX_LIGHTTYPE_SPOT
   pub light_type: i32,
   /// diffuse
   pub diffuse: COLOR_F,
@@ -15,17 +20,70 @@ pub struct LightParamSub {
   pub ambient: COLOR_F,
   /// position (not for direction light)
   pub position: VECTOR,
-  /// direction
-  pub direction: VECTOR
+  /// direction (not for point light)
+  pub direction: VECTOR,
+  /// range
+  pub range: f32,
+  /// fall off
+  pub fall_off: f32,
+  /// attenuation (constant)
+  pub attenuation0: f32,
+  /// attenuation (linear)
+  pub attenuation1: f32,
+  /// attenuation (quadratic)
+  pub attenuation2: f32,
+  /// inner angle of spot
+  pub theta: f32,
+  /// outer angle of spot (0-DX_PI_F)
+  pub phi: f32
 }
 
 /// LightParamSub
 impl LightParamSub {
-  /// constructor
+  /// constructor (range 2000 without attenuation, phi pi/2 theta pi/4)
   pub fn new(light_type: i32,
     diffuse: COLOR_F, specular: COLOR_F, ambient: COLOR_F,
     position: VECTOR, direction: VECTOR) -> Self {
-    LightParamSub{light_type, diffuse, specular, ambient, position, direction}
+    let pi = std::f32::consts::PI;
+    LightParamSub{light_type, diffuse, specular, ambient, position, direction,
+      range: 2000.0, fall_off: 1.0,
+      attenuation0: 1.0, attenuation1: 0.0, attenuation2: 0.0,
+      theta: pi / 4.0, phi: pi / 2.0}
+  }
+  /// with range attenuation
+  pub fn range_atten(mut self, rng: f32, a0: f32, a1: f32, a2: f32) -> Self {
+    self.range = rng;
+    (self.attenuation0, self.attenuation1, self.attenuation2) = (a0, a1, a2);
+    self
+  }
+  /// with angle (oa: phi, ia: theta)
+  pub fn angle(mut self, oa: f32, ia: f32) -> Self {
+    (self.phi, self.theta) = (oa, ia);
+    self
+  }
+  /// as LIGHTPARAM
+  pub fn to_param(&self) -> LIGHTPARAM {
+    LIGHTPARAM{light_type: self.light_type,
+      diffuse: self.diffuse.clone(), specular: self.specular.clone(),
+      ambient: self.ambient.clone(),
+      position: self.position.clone(), direction: self.direction.clone(),
+      range: self.range, fall_off: self.fall_off,
+      attenuation0: self.attenuation0, attenuation1: self.attenuation1,
+      attenuation2: self.attenuation2, theta: self.theta, phi: self.phi}
+  }
+}
+
+/// From LIGHTPARAM for LightParamSub
+impl From<&LIGHTPARAM> for LightParamSub {
+  /// from
+  fn from(p: &LIGHTPARAM) -> Self {
+    LightParamSub{light_type: p.light_type,
+      diffuse: p.diffuse.clone(), specular: p.specular.clone(),
+      ambient: p.ambient.clone(),
+      position: p.position.clone(), direction: p.direction.clone(),
+      range: p.range, fall_off: p.fall_off,
+      attenuation0: p.attenuation0, attenuation1: p.attenuation1,
+      attenuation2: p.attenuation2, theta: p.theta, phi: p.phi}
   }
 }
 
@@ -81,6 +139,43 @@ impl Light {
       h: unsafe { CreatePointLightHandle(p, rng, a0, a1, a2) }
     }
   }
+  /// (move) create the kind of p.light_type and set the colors
+  pub fn from_param(p: &LIGHTPARAM) -> Result<Self, Box<dyn Error>> {
+    let (v, d) = (p.position.clone(), p.direction.clone());
+    let (rng, a0, a1, a2) =
+      (p.range, p.attenuation0, p.attenuation1, p.attenuation2);
+    let l = match p.light_type {
+    DX_LIGHTTYPE_DIRECTIONAL => Light::create_dir(d),
+    DX_LIGHTTYPE_POINT => Light::create_point(v, rng, a0, a1, a2),
+    DX_LIGHTTYPE_SPOT =>
+      Light::create_spot(v, d, p.phi, p.theta, rng, a0, a1, a2),
+    _ => return Err(format!("light: type {}", p.light_type).into())
+    };
+    if l.h == -1 {
+      return Err(format!("light: cannot create type {}", p.light_type).into())
+    }
+    l.set_dif_color(p.diffuse.clone());
+    l.set_spc_color(p.specular.clone());
+    l.set_amb_color(p.ambient.clone());
+    if p.light_type == DX_LIGHTTYPE_DIRECTIONAL {
+      l.set_position(p.position.clone()); // kept (no effect)
+    }
+    Ok(l)
+  }
+  /// read back the parameters (fall_off is always 1.0)
+  pub fn snapshot(&self) -> LIGHTPARAM {
+    let (mut rng, mut a0, mut a1, mut a2) = (0.0, 0.0, 0.0, 0.0);
+    self.get_range_atten(&mut rng, &mut a0, &mut a1, &mut a2);
+    let (mut oa, mut ia) = (0.0, 0.0);
+    self.get_angle(&mut oa, &mut ia);
+    LIGHTPARAM{light_type: self.get_type(),
+      diffuse: self.get_dif_color(), specular: self.get_spc_color(),
+      ambient: self.get_amb_color(),
+      position: self.get_position(), direction: self.get_direction(),
+      range: rng, fall_off: 1.0,
+      attenuation0: a0, attenuation1: a1, attenuation2: a2,
+      theta: ia, phi: oa}
+  }
   /// set type
   pub fn set_type(&self, typ: i32) -> i32 {
     unsafe { SetLightTypeHandle(self.h, typ) }
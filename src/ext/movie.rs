@@ -0,0 +1,79 @@
+//! movie ext dx bridge for DxLib
+//!
+
+use crate::{dx::*, ext::tdx::*};
+use crate::ext::graph::Graph;
+
+/// Movie (graph updated by playing)
+pub struct Movie {
+  /// to be disposed
+  pub d: bool,
+  /// handle (graph)
+  pub h: i32
+}
+
+/// Tr for Movie
+impl Tr for Movie {
+  /// as movie
+  fn as_movie(&self) -> Movie { Movie{d: false, h: self.h} }
+  /// as graph
+  fn as_graph(&self) -> Graph { Graph{d: false, h: self.h} }
+
+  /// handle
+  fn handle(&self) -> i32 { self.h }
+  /// dispose
+  fn dispose(&mut self) {
+    if self.d && self.h != 0 {
+      unsafe { DeleteGraph(self.h, FALSE); }
+      self.h = 0;
+    }
+  }
+}
+
+/// Drop for Movie
+impl Drop for Movie {
+  /// drop
+  fn drop(&mut self) { self.dispose(); }
+}
+
+/// Movie
+impl Movie {
+  /// open (full_color: default TRUE)
+  pub fn open(n: &String, full_color: i32) -> Self {
+    Movie{d: true, h: unsafe { OpenMovieToGraph(n.as_ptr(), full_color) } }
+  }
+  /// graph (not disposed) to draw or set to shader
+  pub fn graph(&self) -> Graph { self.as_graph() }
+  /// play DX_PLAYTYPE_BACK DX_PLAYTYPE_LOOP
+  pub fn play(&self, t: i32) { unsafe { PlayMovieToGraph(self.h, t, 0); } }
+  /// pause
+  pub fn pause(&self) { unsafe { PauseMovieToGraph(self.h, 0); } }
+  /// seek ms
+  pub fn seek(&self, ms: i32) { unsafe { SeekMovieToGraph(self.h, ms); } }
+  /// tell ms
+  pub fn tell(&self) -> i32 { unsafe { TellMovieToGraph(self.h) } }
+  /// state 1: playing 0: stopped
+  pub fn state(&self) -> i32 { unsafe { GetMovieStateToGraph(self.h) } }
+  /// playing
+  pub fn is_playing(&self) -> bool { self.state() == 1 }
+  /// total frame
+  pub fn total_frame(&self) -> i32 {
+    unsafe { GetMovieTotalFrameToGraph(self.h) }
+  }
+  /// speed rate (1.0: normal)
+  pub fn speed(&self, rate: f64) {
+    unsafe { SetPlaySpeedRateMovieToGraph(self.h, rate); }
+  }
+  /// volume 0-255
+  pub fn volume(&self, v: i32) {
+    unsafe { ChangeMovieVolumeToGraph(v, self.h); }
+  }
+  /// draw current frame
+  pub fn draw(&self, x: i32, y: i32, trans: i32) {
+    self.graph().draw(x, y, trans);
+  }
+  /// set current frame to shader
+  pub fn set_to_shader(&self, i: i32) { self.graph().set_to_shader(i); }
+  /// get size
+  pub fn get_size(&self) -> (i32, i32) { self.graph().get_size() }
+}
@@ -0,0 +1,110 @@
+//! mrt ext dx bridge for DxLib
+//!
+//! multiple render targets for the shaders (SV_Target0 SV_Target1 ...)
+//!
+
+use std::error::Error;
+
+use crate::{dx::*, ext::tdx::*};
+use crate::ext::graph::Screen;
+
+/// ScreenFormat (for MakeScreen)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreenFormat {
+  /// channels 1 2 4 (0: default)
+  pub channels: i32,
+  /// bits of a channel 8 16 32 (0: default)
+  pub bits: i32,
+  /// float type
+  pub float: bool
+}
+
+/// ScreenFormat
+impl ScreenFormat {
+  /// default of DxLib
+  pub const DEFAULT: ScreenFormat =
+    ScreenFormat{channels: 0, bits: 0, float: false};
+  /// rgba 8 bits
+  pub const RGBA8: ScreenFormat =
+    ScreenFormat{channels: 4, bits: 8, float: false};
+  /// rgba half float (normal etc)
+  pub const RGBA16F: ScreenFormat =
+    ScreenFormat{channels: 4, bits: 16, float: true};
+  /// rgba float (position etc)
+  pub const RGBA32F: ScreenFormat =
+    ScreenFormat{channels: 4, bits: 32, float: true};
+  /// r float (depth etc)
+  pub const R32F: ScreenFormat =
+    ScreenFormat{channels: 1, bits: 32, float: true};
+  /// make screen of this format (restore the create flags after)
+  pub fn make_screen(&self, w: i32, h: i32, trans: i32) -> Screen {
+    unsafe {
+      let prev = (GetCreateDrawValidGraphChannelNum(),
+        GetCreateGraphChannelBitDepth(),
+        GetDrawValidFloatTypeGraphCreateFlag());
+      SetCreateDrawValidGraphChannelNum(self.channels);
+      SetCreateGraphChannelBitDepth(self.bits);
+      SetDrawValidFloatTypeGraphCreateFlag(self.float as i32);
+      let s = Screen::make(w, h, trans);
+      SetCreateDrawValidGraphChannelNum(prev.0);
+      SetCreateGraphChannelBitDepth(prev.1);
+      SetDrawValidFloatTypeGraphCreateFlag(prev.2);
+      s
+    }
+  }
+}
+
+/// Mrt (binds screens to render targets 0 1 2 ... until dropped)
+/// - targets 1 .. are unbound and the previous draw screen is restored
+pub struct Mrt {
+  /// bound screens (not disposed by Mrt)
+  pub screens: Vec<Screen>,
+  /// draw screen before bind
+  pub prev: i32
+}
+
+/// Mrt
+impl Mrt {
+  /// max number of targets (DX11, DX9 is 4)
+  pub const MAX: usize = 8;
+  /// bind screens to targets 0 .. screens.len() (1 ..= MAX screens)
+  pub fn bind(screens: &[&Screen]) -> Result<Self, Box<dyn Error>> {
+    if screens.is_empty() || screens.len() > Mrt::MAX {
+      return Err(format!("mrt: {} targets out of 1..={}",
+        screens.len(), Mrt::MAX).into())
+    }
+    let prev = unsafe { GetDrawScreen() };
+    for (i, s) in screens.iter().enumerate() {
+      unsafe { SetRenderTargetToShader(i as i32, s.h, 0, 0); }
+    }
+    Ok(Mrt{screens: screens.iter().map(|s| s.as_screen()).collect(), prev})
+  }
+  /// number of targets
+  pub fn len(&self) -> usize { self.screens.len() }
+  /// no targets (never after bind)
+  pub fn is_empty(&self) -> bool { self.screens.is_empty() }
+  /// clear all targets (with the current background color)
+  pub fn clear(&self) {
+    for s in self.screens.iter() {
+      s.with_draw(|_, _| clear_draw_screen(NULL));
+    }
+    self.rebind();
+  }
+  /// bind again (after SetDrawScreen etc reset the targets)
+  pub fn rebind(&self) {
+    for (i, s) in self.screens.iter().enumerate() {
+      unsafe { SetRenderTargetToShader(i as i32, s.h, 0, 0); }
+    }
+  }
+}
+
+/// Drop for Mrt
+impl Drop for Mrt {
+  /// restore single target
+  fn drop(&mut self) {
+    for i in (1..self.screens.len()).rev() {
+      unsafe { SetRenderTargetToShader(i as i32, -1, 0, 0); }
+    }
+    unsafe { SetRenderTargetToShader(0, self.prev, 0, 0); }
+  }
+}
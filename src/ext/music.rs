@@ -1,6 +1,8 @@
 //! music ext dx bridge for DxLib
 //!
 
+use std::ffi::c_void;
+
 use crate::{dx::*, ext::tdx::*};
 
 /// Music
@@ -39,6 +41,11 @@ impl Music {
   pub fn load_mem(n: &String) -> Self {
     Music{d: true, h: unsafe { LoadMusicMem(n.as_ptr()) } }
   }
+  /// from midi file image (include_bytes! etc, kept while playing)
+  pub fn from_bytes(b: &'static [u8]) -> Self {
+    Music{d: true, h: unsafe {
+      LoadMusicMemByMemImage(b.as_ptr() as *const c_void, b.len()) } }
+  }
   /// volume
   pub fn volume(&self, v: i32) { unsafe { SetVolumeMusicMem(v, self.h); } }
   /// stop
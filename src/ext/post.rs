@@ -0,0 +1,144 @@
+//! post ext dx bridge for DxLib
+//!
+//! draw the scene between begin and present
+//!
+//! ```ignore
+//! let mut pc = PostChain::window();
+//! pc.shader(&ps, &[&cb], &[&noise]).filter(Filter::Gauss{
+//!   pixel_width: 16, param: 100});
+//! pc.begin();
+//! // draw the scene
+//! pc.present(&Screen::back());
+//! ```
+//!
+//! passes run in order on two screens (see ping_pong)
+//!
+
+use crate::{dx::*, ext::tdx::*};
+use crate::ext::graph::{Screen, Graph};
+use crate::ext::shader::{ConstantBuffer, PixelShader};
+use crate::ext::filter::Filter;
+use crate::ext::batch::Batch2D;
+
+/// Pass
+pub enum Pass {
+  /// pixel shader (source on stage 0, inputs on stage 1 2 ...)
+  Shader{
+    /// pixel shader (not disposed by Pass)
+    ps: PixelShader,
+    /// constant buffers set on each run (updated when written, read only)
+    cbs: Vec<ConstantBuffer>,
+    /// extra textures (not disposed by Pass)
+    inputs: Vec<Graph>
+  },
+  /// built-in filter
+  Filter(Filter)
+}
+
+/// (source, destination) screen indices of each pass from the start
+/// - the result is on the destination of the last pass (start when empty)
+pub fn ping_pong(start: usize, n: usize) -> Vec<(usize, usize)> {
+  (0..n).map(|i| ((start + i) % 2, (start + i + 1) % 2)).collect()
+}
+
+/// PostChain (ping-pong offscreen screens)
+pub struct PostChain {
+  /// w
+  pub w: i32,
+  /// h
+  pub h: i32,
+  /// ping-pong screens
+  pub screens: [Screen; 2],
+  /// index of the source of the next pass (the scene before run)
+  pub cur: usize,
+  /// passes
+  pub passes: Vec<Pass>
+}
+
+/// PostChain
+impl PostChain {
+  /// constructor (screens of w h)
+  pub fn new(w: i32, h: i32) -> Self {
+    PostChain{w, h, screens: [Screen::make(w, h, FALSE),
+      Screen::make(w, h, FALSE)], cur: 0, passes: vec![]}
+  }
+  /// constructor (the size of the back buffer)
+  pub fn window() -> Self {
+    let (w, h) = Screen::back().with_draw(|w, h| (w, h));
+    PostChain::new(w, h)
+  }
+  /// recreate screens when the size is changed (keep passes)
+  pub fn resize(&mut self, w: i32, h: i32) {
+    if (w, h) == (self.w, self.h) { return }
+    (self.w, self.h) = (w, h);
+    self.screens = [Screen::make(w, h, FALSE), Screen::make(w, h, FALSE)];
+  }
+  /// source of the next pass (the result after run)
+  pub fn src(&self) -> &Screen { &self.screens[self.cur % 2] }
+  /// add pass
+  pub fn add(&mut self, p: Pass) -> &mut Self {
+    self.passes.push(p);
+    self
+  }
+  /// add pixel shader pass
+  pub fn shader(&mut self, ps: &PixelShader, cbs: &[&ConstantBuffer],
+    inputs: &[&Graph]) -> &mut Self {
+    self.add(Pass::Shader{ps: ps.as_pixel_shader(),
+      cbs: cbs.iter().map(|cb| cb.as_constant_buffer()).collect(),
+      inputs: inputs.iter().map(|g| g.as_graph()).collect()})
+  }
+  /// add filter pass
+  pub fn filter(&mut self, f: Filter) -> &mut Self {
+    self.add(Pass::Filter(f))
+  }
+  /// set src as the draw screen and clear it (draw the scene after this)
+  pub fn begin(&self) {
+    self.src().set_draw();
+    clear_draw_screen(NULL);
+  }
+  /// run all passes in order (result is src)
+  /// - DxLib default pixel shader is set after shader passes
+  pub fn run(&mut self) -> &Screen {
+    let none = Graph{d: false, h: -1};
+    let plan = ping_pong(self.cur % 2, self.passes.len());
+    for (p, &(s, d)) in self.passes.iter().zip(plan.iter()) {
+      let (src, dst) = (&self.screens[s], &self.screens[d]);
+      match p {
+      Pass::Shader{ps, cbs, inputs} => {
+        let (w, h) = (self.w as f32, self.h as f32);
+        let wh = COLOR_U8::new(255, 255, 255, 255);
+        let mut q = Batch2D::triangles();
+        q.quad_uv([0.0, 0.0, w, h], [0.0, 0.0, 1.0, 1.0], [&wh; 4]);
+        dst.with_draw(|_, _| {
+          ps.set_shader();
+          for cb in cbs.iter() {
+            if cb.dirty() { cb.update(); }
+            ps.set_const(cb);
+          }
+          src.graph().set_to_shader(0);
+          for (s, g) in inputs.iter().enumerate() {
+            g.set_to_shader(s as i32 + 1);
+          }
+          q.draw_to_shader();
+          for s in 0..=inputs.len() as i32 {
+            none.set_to_shader(s); // unbind the screens
+          }
+        });
+      },
+      Pass::Filter(f) => { src.filter_to(dst, f); }
+      }
+    }
+    if self.passes.iter().any(|p| matches!(p, Pass::Shader{..})) {
+      unsafe { SetUsePixelShader(-1); } // no getter of the previous one
+    }
+    if let Some(&(_, d)) = plan.last() { self.cur = d; }
+    self.src()
+  }
+  /// run all passes and draw the result to the left top of to
+  /// (to is left as the draw screen)
+  pub fn present(&mut self, to: &Screen) {
+    let r = self.run().graph();
+    to.set_draw();
+    r.draw(0, 0, FALSE);
+  }
+}
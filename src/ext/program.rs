@@ -0,0 +1,171 @@
+//! program ext dx bridge for DxLib
+//!
+//! shaders constant buffers and textures applied at once
+//!
+//! ```ignore
+//! let prog = ProgramDesc::new().vertex(&shv).pixel(&shp)
+//!   .constant(DX_SHADERTYPE_VERTEX, &cbv)
+//!   .constant(DX_SHADERTYPE_PIXEL, &cb8)
+//!   .texture(0, &tex).build()?;
+//! prog.bind(); // set shaders, update written buffers and set, set textures
+//! // draw_polygon_3d_to_shader etc
+//! prog.unbind(); // DxLib default shaders
+//! ```
+//!
+
+use std::error::Error;
+
+use crate::{dx::*, ext::tdx::*};
+use crate::ext::graph::Graph;
+use crate::ext::shader::{ConstantBuffer, VertexShader, PixelShader};
+use crate::ext::shader::GeometryShader;
+
+/// number of constant buffer slots of a shader (DX11)
+pub const CB_SLOTS: i32 = 14;
+/// number of texture stages
+pub const TEX_STAGES: i32 = 16;
+
+/// name of shader type
+fn stage_name(ts: i32) -> &'static str {
+  match ts {
+  DX_SHADERTYPE_VERTEX => "vertex",
+  DX_SHADERTYPE_PIXEL => "pixel",
+  DX_SHADERTYPE_GEOMETRY => "geometry",
+  _ => "unknown"
+  }
+}
+
+/// Binding of a constant buffer
+pub struct CbBinding {
+  /// DX_SHADERTYPE_VERTEX DX_SHADERTYPE_PIXEL DX_SHADERTYPE_GEOMETRY
+  pub ts: i32,
+  /// slot (register bN)
+  pub slot: i32,
+  /// constant buffer (not disposed, read only)
+  pub cb: ConstantBuffer
+}
+
+/// ProgramDesc (builder of ShaderProgram, nothing is disposed)
+#[derive(Default)]
+pub struct ProgramDesc {
+  /// vertex shader
+  pub vs: Option<VertexShader>,
+  /// pixel shader
+  pub ps: Option<PixelShader>,
+  /// geometry shader
+  pub gs: Option<GeometryShader>,
+  /// constant buffers
+  pub cbs: Vec<CbBinding>,
+  /// textures (stage, graph)
+  pub textures: Vec<(i32, Graph)>
+}
+
+/// ProgramDesc
+impl ProgramDesc {
+  /// constructor
+  pub fn new() -> Self { ProgramDesc::default() }
+  /// with vertex shader
+  pub fn vertex(mut self, vs: &VertexShader) -> Self {
+    self.vs = Some(vs.as_vertex_shader());
+    self
+  }
+  /// with pixel shader
+  pub fn pixel(mut self, ps: &PixelShader) -> Self {
+    self.ps = Some(ps.as_pixel_shader());
+    self
+  }
+  /// with geometry shader
+  pub fn geometry(mut self, gs: &GeometryShader) -> Self {
+    self.gs = Some(gs.as_geometry_shader());
+    self
+  }
+  /// with constant buffer on the slot cb.s of the shader type ts
+  pub fn constant<T: 'static>(self, ts: i32, cb: &ConstantBuffer<T>) -> Self {
+    self.constant_at(ts, cb.s, cb)
+  }
+  /// with constant buffer on the slot of the shader type ts
+  pub fn constant_at<T: 'static>(mut self, ts: i32, slot: i32,
+    cb: &ConstantBuffer<T>) -> Self {
+    self.cbs.push(CbBinding{ts, slot, cb: cb.as_constant_buffer()});
+    self
+  }
+  /// with texture on the stage
+  pub fn texture(mut self, stage: i32, g: &Graph) -> Self {
+    self.textures.push((stage, g.as_graph()));
+    self
+  }
+  /// check shaders slots and stages
+  pub fn build(self) -> Result<ShaderProgram, Box<dyn Error>> {
+    if self.vs.is_none() && self.ps.is_none() && self.gs.is_none() {
+      return Err("shader program: no shader".into())
+    }
+    for (i, b) in self.cbs.iter().enumerate() {
+      let has = match b.ts {
+      DX_SHADERTYPE_VERTEX => self.vs.is_some(),
+      DX_SHADERTYPE_PIXEL => self.ps.is_some(),
+      DX_SHADERTYPE_GEOMETRY => self.gs.is_some(),
+      _ => return Err(format!("shader program: shader type {}", b.ts).into())
+      };
+      if !has {
+        return Err(format!("shader program: no {} shader for b{}",
+          stage_name(b.ts), b.slot).into())
+      }
+      if !(0..CB_SLOTS).contains(&b.slot) {
+        return Err(format!("shader program: {} slot b{} out of 0..{}",
+          stage_name(b.ts), b.slot, CB_SLOTS).into())
+      }
+      if let Some(o) = self.cbs[..i].iter()
+        .find(|o| (o.ts, o.slot) == (b.ts, b.slot)) {
+        return Err(format!("shader program: {} slot b{} {:08x} and {:08x}",
+          stage_name(b.ts), b.slot, o.cb.h, b.cb.h).into())
+      }
+    }
+    for (i, (s, g)) in self.textures.iter().enumerate() {
+      if !(0..TEX_STAGES).contains(s) {
+        return Err(format!("shader program: stage {} out of 0..{}",
+          s, TEX_STAGES).into())
+      }
+      if let Some((_, o)) = self.textures[..i].iter().find(|(t, _)| t == s) {
+        return Err(format!("shader program: stage {} {:08x} and {:08x}",
+          s, o.h, g.h).into())
+      }
+    }
+    Ok(ShaderProgram{desc: self})
+  }
+}
+
+/// ShaderProgram (checked ProgramDesc)
+pub struct ShaderProgram {
+  /// desc
+  desc: ProgramDesc
+}
+
+/// ShaderProgram
+impl ShaderProgram {
+  /// desc
+  pub fn desc(&self) -> &ProgramDesc { &self.desc }
+  /// set shaders, update (when written) and set constant buffers, set textures
+  pub fn bind(&self) {
+    let d = &self.desc;
+    if let Some(vs) = &d.vs { vs.set_shader(); }
+    if let Some(ps) = &d.ps { ps.set_shader(); }
+    if let Some(gs) = &d.gs { gs.set_shader(); }
+    for b in d.cbs.iter() {
+      if b.cb.dirty() { b.cb.update(); }
+      b.cb.set_to_slot(b.ts, b.slot);
+    }
+    for (s, g) in d.textures.iter() { g.set_to_shader(*s); }
+  }
+  /// release textures and restore DxLib default shaders
+  pub fn unbind(&self) {
+    let d = &self.desc;
+    for (s, _) in d.textures.iter() {
+      unsafe { SetUseTextureToShader(*s, -1); }
+    }
+    unsafe {
+      if d.vs.is_some() { SetUseVertexShader(-1); }
+      if d.ps.is_some() { SetUsePixelShader(-1); }
+      if d.gs.is_some() { SetUseGeometryShader(-1); }
+    }
+  }
+}
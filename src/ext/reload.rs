@@ -0,0 +1,91 @@
+//! reload ext dx bridge for DxLib
+//!
+//! hot reload of shaders (.vso .pso .gso) and textures by modified time
+//! - call Tdx::reload_changed in the main loop
+//! - a graph is reloaded into the same handle (ReloadGraph)
+//! - a shader is loaded as a new handle, the registry entry is swapped and
+//!   the copies (VertexShader etc) refer to it by current_shader
+//!   (until the new shader is disposed)
+//!
+
+use std::time::{Duration, Instant, SystemTime};
+
+/// ReloadKind
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReloadKind {
+  /// VertexShader
+  Vertex,
+  /// PixelShader
+  Pixel,
+  /// GeometryShader
+  Geometry,
+  /// Graph
+  Graph
+}
+
+/// Watch
+#[derive(Debug, Clone, PartialEq)]
+pub struct Watch {
+  /// file name (end with 0)
+  pub n: String,
+  /// kind
+  pub kind: ReloadKind,
+  /// handle (key of the registry)
+  pub h: i32,
+  /// modified time at load or the last reload
+  pub mtime: Option<SystemTime>
+}
+
+/// HotReload
+#[derive(Debug, Clone)]
+pub struct HotReload {
+  /// watched files
+  pub watches: Vec<Watch>,
+  /// minimum interval of polling
+  pub interval: Duration,
+  /// last polling
+  pub last: Option<Instant>
+}
+
+/// Default for HotReload
+impl Default for HotReload {
+  /// polling every 500ms
+  fn default() -> Self {
+    HotReload{watches: vec![], interval: Duration::from_millis(500),
+      last: None}
+  }
+}
+
+/// modified time of the file (None: not found)
+pub fn mtime(n: &str) -> Option<SystemTime> {
+  std::fs::metadata(n.trim_end_matches('\0')).and_then(|m| m.modified()).ok()
+}
+
+/// HotReload
+impl HotReload {
+  /// watch n (replaces the watch of the same handle)
+  pub fn watch(&mut self, n: &String, kind: ReloadKind, h: i32) {
+    self.watches.retain(|w| w.h != h);
+    self.watches.push(Watch{n: n.clone(), kind, h, mtime: mtime(n)});
+  }
+  /// stop watching the handle
+  pub fn unwatch(&mut self, h: i32) {
+    self.watches.retain(|w| w.h != h);
+  }
+  /// indices of the watches changed since the last poll (after interval)
+  /// - the new modified time is recorded (a failed reload is not retried
+  ///   until the file is changed again)
+  pub fn poll(&mut self) -> Vec<usize> {
+    let now = Instant::now();
+    if self.last.is_some_and(|t| now.duration_since(t) < self.interval) {
+      return vec![]
+    }
+    self.last = Some(now);
+    self.watches.iter_mut().enumerate().filter_map(|(i, w)| {
+      let t = mtime(&w.n);
+      if t.is_none() || t == w.mtime { return None } // removed while saving
+      w.mtime = t;
+      Some(i)
+    }).collect()
+  }
+}
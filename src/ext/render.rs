@@ -0,0 +1,171 @@
+//! render ext dx bridge for DxLib
+//!
+
+use std::error::Error;
+
+use crate::{dx::*, ext::tdx::*};
+
+/// RenderState (global pipeline state)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderState {
+  /// DX_BLENDMODE_NOBLEND DX_BLENDMODE_ALPHA etc
+  pub blend_mode: i32,
+  /// blend param 0-255
+  pub blend_param: i32,
+  /// use z buffer 3D
+  pub use_z_buffer: i32,
+  /// write z buffer 3D
+  pub write_z_buffer: i32,
+  /// back culling
+  pub back_culling: i32,
+  /// lighting
+  pub lighting: i32,
+  /// specular
+  pub specular: i32,
+  /// global ambient light
+  pub global_ambient: COLOR_F
+}
+
+/// RenderChange (one global call)
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenderChange {
+  /// SetDrawBlendMode
+  BlendMode(i32, i32),
+  /// SetUseZBuffer3D
+  UseZBuffer(i32),
+  /// SetWriteZBuffer3D
+  WriteZBuffer(i32),
+  /// SetUseBackCulling
+  BackCulling(i32),
+  /// SetUseLighting
+  Lighting(i32),
+  /// SetUseSpecular
+  Specular(i32),
+  /// SetGlobalAmbientLight
+  GlobalAmbient(COLOR_F)
+}
+
+/// RenderChange
+impl RenderChange {
+  /// call DxLib
+  pub fn apply(&self) -> i32 {
+    match self {
+    RenderChange::BlendMode(bm, pal) => set_draw_blend_mode(*bm, *pal),
+    RenderChange::UseZBuffer(f) => set_use_z_buffer_3d(*f),
+    RenderChange::WriteZBuffer(f) => set_write_z_buffer_3d(*f),
+    RenderChange::BackCulling(f) => set_use_back_culling(*f),
+    RenderChange::Lighting(f) => set_use_lighting(*f),
+    RenderChange::Specular(f) => set_use_specular(*f),
+    RenderChange::GlobalAmbient(c) => set_global_ambient_light(c.clone())
+    }
+  }
+}
+
+/// Default for RenderState
+impl Default for RenderState {
+  /// DxLib defaults
+  fn default() -> Self {
+    RenderState{blend_mode: DX_BLENDMODE_NOBLEND, blend_param: 0,
+      use_z_buffer: FALSE, write_z_buffer: FALSE, back_culling: FALSE,
+      lighting: TRUE, specular: TRUE, global_ambient: COLOR_F::zeros()}
+  }
+}
+
+/// RenderState
+impl RenderState {
+  /// DxLib defaults
+  pub fn new() -> Self { RenderState::default() }
+  /// changes from prev (all when prev is None)
+  pub fn diff(&self, prev: Option<&RenderState>) -> Vec<RenderChange> {
+    let all = prev.is_none();
+    let p = prev.cloned().unwrap_or_default();
+    let mut v = vec![];
+    if all || p.blend_mode != self.blend_mode
+      || p.blend_param != self.blend_param {
+      v.push(RenderChange::BlendMode(self.blend_mode, self.blend_param));
+    }
+    if all || p.use_z_buffer != self.use_z_buffer {
+      v.push(RenderChange::UseZBuffer(self.use_z_buffer));
+    }
+    if all || p.write_z_buffer != self.write_z_buffer {
+      v.push(RenderChange::WriteZBuffer(self.write_z_buffer));
+    }
+    if all || p.back_culling != self.back_culling {
+      v.push(RenderChange::BackCulling(self.back_culling));
+    }
+    if all || p.lighting != self.lighting {
+      v.push(RenderChange::Lighting(self.lighting));
+    }
+    if all || p.specular != self.specular {
+      v.push(RenderChange::Specular(self.specular));
+    }
+    if all || p.global_ambient != self.global_ambient {
+      v.push(RenderChange::GlobalAmbient(self.global_ambient.clone()));
+    }
+    v
+  }
+  /// apply all settings
+  pub fn apply(&self) -> usize { self.apply_diff(None) }
+  /// apply only changes from prev (returns number of calls)
+  pub fn apply_diff(&self, prev: Option<&RenderState>) -> usize {
+    let v = self.diff(prev);
+    for c in v.iter() { c.apply(); }
+    v.len()
+  }
+}
+
+/// RenderStack (last applied state and saved states)
+#[derive(Debug, Clone, Default)]
+pub struct RenderStack {
+  /// last applied (None: unknown, apply all next time)
+  pub cur: Option<RenderState>,
+  /// saved by push
+  pub stk: Vec<RenderState>
+}
+
+/// RenderStack
+impl RenderStack {
+  /// constructor
+  pub fn new() -> Self { RenderStack::default() }
+  /// apply only changes from the last applied state
+  pub fn apply(&mut self, rs: &RenderState) -> usize {
+    let n = rs.apply_diff(self.cur.as_ref());
+    self.cur = Some(rs.clone());
+    n
+  }
+  /// save the last applied state and apply rs
+  /// - error when no state is applied (DxLib state can not be read back)
+  pub fn push(&mut self, rs: &RenderState) -> Result<usize, Box<dyn Error>> {
+    let cur = self.cur.clone()
+      .ok_or("render stack: push before apply (state is unknown)")?;
+    self.stk.push(cur);
+    Ok(self.apply(rs))
+  }
+  /// restore the state saved by push
+  pub fn pop(&mut self) -> usize {
+    match self.stk.pop() {
+    None => 0,
+    Some(rs) => self.apply(&rs)
+    }
+  }
+  /// push and pop when the result is dropped
+  pub fn scoped(&mut self, rs: &RenderState)
+    -> Result<RenderScope<'_>, Box<dyn Error>> {
+    self.push(rs)?;
+    Ok(RenderScope{stack: self})
+  }
+  /// forget the last applied state (after other code changed it)
+  pub fn invalidate(&mut self) { self.cur = None; }
+}
+
+/// RenderScope (pop on drop)
+pub struct RenderScope<'a> {
+  /// stack
+  pub stack: &'a mut RenderStack
+}
+
+/// Drop for RenderScope
+impl Drop for RenderScope<'_> {
+  /// drop
+  fn drop(&mut self) { self.stack.pop(); }
+}
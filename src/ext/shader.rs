@@ -1,10 +1,23 @@
 //! shader ext dx bridge for DxLib
 //!
 
+use std::ffi::c_void;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::BTreeMap;
+
 use crate::{dx::*, ext::tdx::*};
+use crate::ext::cbuffer::{CbLayout, REGISTER};
 
 /// ConstantBuffer for DX11
-pub struct ConstantBuffer {
+/// - T: FLOAT4 (raw array of FLOAT4) or a struct declared by cbuffer!
+/// - only one of the copies is writable and has the slices
+///   (the others are read only and only pass the handle to DxLib)
+pub struct ConstantBuffer<T = FLOAT4> {
   /// to be disposed
   pub d: bool,
   /// handle
@@ -12,31 +25,30 @@ pub struct ConstantBuffer {
   /// n: number of FLOAT4 (alloc n * 4 * sizeof f32)
   pub n: i32,
   /// s: slot on the shader
-  pub s: i32
+  pub s: i32,
+  /// type of the contents
+  pub t: PhantomData<T>,
+  /// writable
+  w: bool,
+  /// written and not updated (shared by the copies)
+  dirty: Rc<Cell<bool>>
 }
 
 /// Tr for ConstantBuffer
-impl Tr for ConstantBuffer {
-  /// as constant buffer
-  fn as_constant_buffer(&self) -> ConstantBuffer {
-    ConstantBuffer{d: false, h: self.h, n: self.n, s: self.s}
-  }
+impl<T: 'static> Tr for ConstantBuffer<T> {
+  /// as constant buffer (raw, read only)
+  fn as_constant_buffer(&self) -> ConstantBuffer { self.copy(false) }
 
   /// handle
   fn handle(&self) -> i32 { self.h }
   /// dispose
-  fn dispose(&mut self) {
-    if self.d && self.h != 0{
-      unsafe { DeleteShaderConstantBuffer(self.h); }
-      self.h = 0;
-    }
-  }
+  fn dispose(&mut self) { self.delete(); }
 }
 
 /// Drop for ConstantBuffer
-impl Drop for ConstantBuffer {
+impl<T> Drop for ConstantBuffer<T> {
   /// drop
-  fn drop(&mut self) { self.dispose(); }
+  fn drop(&mut self) { self.delete(); }
 }
 
 /// ConstantBuffer for DX11
@@ -46,18 +58,98 @@ impl ConstantBuffer {
   /// - s: slot
   pub fn create(n: i32, s: i32) -> Self {
     let sz = n * std::mem::size_of::<FLOAT4>() as i32;
-    ConstantBuffer{d: true, h: unsafe { CreateShaderConstantBuffer(sz) }, n, s}
+    ConstantBuffer::new(true, unsafe { CreateShaderConstantBuffer(sz) }, n, s)
+  }
+}
+
+/// typed ConstantBuffer for DX11
+impl<T: CbLayout> ConstantBuffer<T> {
+  /// create (size of T)
+  /// - s: slot
+  pub fn create_typed(s: i32) -> Self {
+    let n = (std::mem::size_of::<T>() / REGISTER) as i32;
+    ConstantBuffer::new(true,
+      unsafe { CreateShaderConstantBuffer(n * REGISTER as i32) }, n, s)
+  }
+  /// copy v to the buffer (call update after, or use map)
+  pub fn write(&mut self, v: &T) {
+    let p = self.ptr_mut();
+    assert!(!p.is_null(), "constant buffer {:08x} has no buffer", self.h);
+    unsafe { std::ptr::copy_nonoverlapping(v as *const T as *const u8,
+      p as *mut u8, std::mem::size_of::<T>()); }
+  }
+}
+
+/// ConstantBuffer for DX11
+impl<T> ConstantBuffer<T> {
+  /// constructor (writable, d: to be disposed)
+  /// - h: handle created by CreateShaderConstantBuffer
+  pub fn new(d: bool, h: i32, n: i32, s: i32) -> Self {
+    ConstantBuffer{d, h, n, s, t: PhantomData, w: true,
+      dirty: Rc::new(Cell::new(true))}
   }
-  /// as slice mut
-  pub fn as_slice_mut(&self) -> &mut [FLOAT4] {
-    unsafe { std::slice::from_raw_parts_mut(self.ptr_mut(), self.n as usize) }
+  /// not disposed copy
+  fn copy<U>(&self, w: bool) -> ConstantBuffer<U> {
+    ConstantBuffer{d: false, h: self.h, n: self.n, s: self.s, t: PhantomData,
+      w, dirty: self.dirty.clone()}
   }
-  /// prt mut
-  pub fn ptr_mut(&self) -> *mut FLOAT4 {
+  /// move the write permission to a not disposed copy (self is read only)
+  pub fn writer(&mut self) -> Self {
+    let w = self.w;
+    self.w = false;
+    self.copy(w)
+  }
+  /// writable (false: read only copy)
+  pub fn writable(&self) -> bool { self.w }
+  /// written and not updated
+  pub fn dirty(&self) -> bool { self.dirty.get() }
+  /// delete (any T, Drop can not require T: 'static as Tr)
+  fn delete(&mut self) {
+    if self.d && self.h != 0 {
+      unsafe { DeleteShaderConstantBuffer(self.h); }
+      self.h = 0;
+    }
+  }
+  /// view as another type (read only, U must fit in the buffer)
+  pub fn cast<U: CbLayout>(&self) -> ConstantBuffer<U> {
+    assert!(std::mem::size_of::<U>() <= self.n as usize * REGISTER,
+      "constant buffer of {} FLOAT4 is too small", self.n);
+    self.copy(false)
+  }
+  /// as slice mut (update after, or use map)
+  pub fn as_slice_mut(&mut self) -> &mut [FLOAT4] {
+    let p = self.ptr_mut();
+    if p.is_null() || self.n <= 0 { return &mut [] }
+    unsafe { std::slice::from_raw_parts_mut(p, self.n as usize) }
+  }
+  /// read only view (for debug etc, empty on read only copies)
+  pub fn view(&self) -> &[FLOAT4] {
+    let p = self.ptr();
+    if !self.w || p.is_null() || self.n <= 0 { return &[] }
+    unsafe { std::slice::from_raw_parts(p, self.n as usize) }
+  }
+  /// write guard (update on drop when written)
+  pub fn map(&mut self) -> CbMap<'_, T> {
+    self.check_writable();
+    CbMap{cb: self}
+  }
+  /// ptr
+  pub fn ptr(&self) -> *const FLOAT4 {
+    unsafe { GetBufferShaderConstantBuffer(self.h) }
+  }
+  /// ptr mut (dirty, panics on read only copies)
+  pub fn ptr_mut(&mut self) -> *mut FLOAT4 {
+    self.check_writable();
+    self.dirty.set(true);
     unsafe { GetBufferShaderConstantBuffer(self.h) }
   }
-  /// update
+  /// panic on read only copies
+  fn check_writable(&self) {
+    assert!(self.w, "constant buffer {:08x} is a read only copy", self.h);
+  }
+  /// update (clear dirty)
   pub fn update(&self) -> i32 {
+    self.dirty.set(false);
     unsafe { UpdateShaderConstantBuffer(self.h) }
   }
   /// set to slot (use shader.set_const(&cb) instead of this inner function)
@@ -68,6 +160,96 @@ impl ConstantBuffer {
   }
 }
 
+/// CbMap (write guard of ConstantBuffer)
+/// - deref as FLOAT4 registers, get get_mut as T
+/// - UpdateShaderConstantBuffer on drop when written
+pub struct CbMap<'a, T> {
+  /// buffer
+  cb: &'a mut ConstantBuffer<T>
+}
+
+/// Deref for CbMap
+impl<T> Deref for CbMap<'_, T> {
+  type Target = [FLOAT4];
+  /// registers
+  fn deref(&self) -> &[FLOAT4] { self.cb.view() }
+}
+
+/// DerefMut for CbMap
+impl<T> DerefMut for CbMap<'_, T> {
+  /// registers (dirty)
+  fn deref_mut(&mut self) -> &mut [FLOAT4] {
+    self.cb.as_slice_mut()
+  }
+}
+
+/// CbMap
+impl<T: CbLayout> CbMap<'_, T> {
+  /// pointer to T
+  fn ptr(&self) -> *mut T {
+    let p = self.cb.ptr() as *mut T;
+    assert!(!p.is_null(), "constant buffer {:08x} has no buffer", self.cb.h);
+    assert!(std::mem::size_of::<T>() <= self.cb.n as usize * REGISTER
+      && p.is_aligned(), "constant buffer {:08x} does not fit", self.cb.h);
+    p
+  }
+  /// as T
+  pub fn get(&self) -> &T { unsafe { &*self.ptr() } }
+  /// as T mut (dirty)
+  pub fn get_mut(&mut self) -> &mut T {
+    self.cb.dirty.set(true);
+    unsafe { &mut *self.ptr() }
+  }
+}
+
+/// Drop for CbMap
+impl<T> Drop for CbMap<'_, T> {
+  /// update when written
+  fn drop(&mut self) {
+    if self.cb.dirty() { self.cb.update(); }
+  }
+}
+
+/// shader handles replaced by hot reload (old: new)
+static SWAPPED: Mutex<BTreeMap<i32, i32>> = Mutex::new(BTreeMap::new());
+/// SWAPPED is not empty (no lock until the first hot reload)
+static SWAPPED_ANY: AtomicBool = AtomicBool::new(false);
+
+/// update SWAPPED
+fn swapped(f: impl FnOnce(&mut BTreeMap<i32, i32>)) {
+  if let Ok(mut m) = SWAPPED.lock() {
+    f(&mut m);
+    SWAPPED_ANY.store(!m.is_empty(), Ordering::Release);
+  }
+}
+
+/// current handle of the shader (new handle after hot reload)
+pub fn current_shader(h: i32) -> i32 {
+  if !SWAPPED_ANY.load(Ordering::Acquire) { return h }
+  SWAPPED.lock().map(|m| m.get(&h).copied().unwrap_or(h)).unwrap_or(h)
+}
+
+/// old handle (and the handles replaced by old) refer to new
+pub fn swap_shader(old: i32, new: i32) {
+  swapped(|m| {
+    m.remove(&new); // value of a deleted handle may be used again
+    for v in m.values_mut() { if *v == old { *v = new; } }
+    m.insert(old, new);
+  });
+}
+
+/// forget the handles refer to h (h is deleted)
+pub fn release_shader(h: i32) {
+  if !SWAPPED_ANY.load(Ordering::Acquire) { return }
+  swapped(|m| m.retain(|_, v| *v != h));
+}
+
+/// new shader handle h is not an old handle of another shader
+fn fresh(h: i32) -> i32 {
+  if SWAPPED_ANY.load(Ordering::Acquire) { swapped(|m| { m.remove(&h); }); }
+  h
+}
+
 /// VertexShader
 pub struct VertexShader {
   /// to be disposed
@@ -83,11 +265,12 @@ impl Tr for VertexShader {
     VertexShader{d: false, h: self.h}
   }
 
-  /// handle
-  fn handle(&self) -> i32 { self.h }
+  /// handle (current after hot reload)
+  fn handle(&self) -> i32 { current_shader(self.h) }
   /// dispose
   fn dispose(&mut self) {
     if self.d && self.h != 0 {
+      release_shader(self.h);
       unsafe { DeleteShader(self.h); }
       self.h = 0;
     }
@@ -96,8 +279,10 @@ impl Tr for VertexShader {
 
 /// Ts for VertexShader
 impl Ts for VertexShader {
+  /// DX_SHADERTYPE_VERTEX
+  fn shader_type(&self) -> i32 { DX_SHADERTYPE_VERTEX }
   /// for DX11
-  fn set_const(&self, cb: &ConstantBuffer) -> i32 {
+  fn set_const<T>(&self, cb: &ConstantBuffer<T>) -> i32 {
     cb.set_to_slot(DX_SHADERTYPE_VERTEX, cb.s)
   }
 }
@@ -112,11 +297,16 @@ impl Drop for VertexShader {
 impl VertexShader {
   /// load
   pub fn load(n: &String) -> Self {
-    VertexShader{d: true, h: unsafe { LoadVertexShader(n.as_ptr()) } }
+    VertexShader{d: true, h: fresh(unsafe { LoadVertexShader(n.as_ptr()) }) }
+  }
+  /// from .vso file image (include_bytes! etc)
+  pub fn from_bytes(b: &[u8]) -> Self {
+    VertexShader{d: true, h: fresh(unsafe { LoadVertexShaderFromMem(
+      b.as_ptr() as *const c_void, b.len() as i32) }) }
   }
   /// set shader
   pub fn set_shader(&self) {
-    unsafe { SetUseVertexShader(self.h); }
+    unsafe { SetUseVertexShader(self.handle()); }
   }
 }
 
@@ -135,11 +325,12 @@ impl Tr for PixelShader {
     PixelShader{d: false, h: self.h}
   }
 
-  /// handle
-  fn handle(&self) -> i32 { self.h }
+  /// handle (current after hot reload)
+  fn handle(&self) -> i32 { current_shader(self.h) }
   /// dispose
   fn dispose(&mut self) {
     if self.d && self.h != 0 {
+      release_shader(self.h);
       unsafe { DeleteShader(self.h); }
       self.h = 0;
     }
@@ -148,8 +339,10 @@ impl Tr for PixelShader {
 
 /// Ts for PixelShader
 impl Ts for PixelShader {
+  /// DX_SHADERTYPE_PIXEL
+  fn shader_type(&self) -> i32 { DX_SHADERTYPE_PIXEL }
   /// for DX11
-  fn set_const(&self, cb: &ConstantBuffer) -> i32 {
+  fn set_const<T>(&self, cb: &ConstantBuffer<T>) -> i32 {
     cb.set_to_slot(DX_SHADERTYPE_PIXEL, cb.s)
   }
 }
@@ -164,11 +357,16 @@ impl Drop for PixelShader {
 impl PixelShader {
   /// load
   pub fn load(n: &String) -> Self {
-    PixelShader{d: true, h: unsafe { LoadPixelShader(n.as_ptr()) } }
+    PixelShader{d: true, h: fresh(unsafe { LoadPixelShader(n.as_ptr()) }) }
+  }
+  /// from .pso file image (include_bytes! etc)
+  pub fn from_bytes(b: &[u8]) -> Self {
+    PixelShader{d: true, h: fresh(unsafe { LoadPixelShaderFromMem(
+      b.as_ptr() as *const c_void, b.len() as i32) }) }
   }
   /// set shader
   pub fn set_shader(&self) {
-    unsafe { SetUsePixelShader(self.h); }
+    unsafe { SetUsePixelShader(self.handle()); }
   }
 }
 
@@ -187,11 +385,12 @@ impl Tr for GeometryShader {
     GeometryShader{d: false, h: self.h}
   }
 
-  /// handle
-  fn handle(&self) -> i32 { self.h }
+  /// handle (current after hot reload)
+  fn handle(&self) -> i32 { current_shader(self.h) }
   /// dispose
   fn dispose(&mut self) {
     if self.d && self.h != 0 {
+      release_shader(self.h);
       unsafe { DeleteShader(self.h); }
       self.h = 0;
     }
@@ -200,8 +399,10 @@ impl Tr for GeometryShader {
 
 /// Ts for GeometryShader
 impl Ts for GeometryShader {
+  /// DX_SHADERTYPE_GEOMETRY
+  fn shader_type(&self) -> i32 { DX_SHADERTYPE_GEOMETRY }
   /// for DX11
-  fn set_const(&self, cb: &ConstantBuffer) -> i32 {
+  fn set_const<T>(&self, cb: &ConstantBuffer<T>) -> i32 {
     cb.set_to_slot(DX_SHADERTYPE_GEOMETRY, cb.s)
   }
 }
@@ -216,10 +417,16 @@ impl Drop for GeometryShader {
 impl GeometryShader {
   /// load
   pub fn load(n: &String) -> Self {
-    GeometryShader{d: true, h: unsafe { LoadGeometryShader(n.as_ptr()) } }
+    GeometryShader{d: true,
+      h: fresh(unsafe { LoadGeometryShader(n.as_ptr()) }) }
+  }
+  /// from .gso file image (include_bytes! etc)
+  pub fn from_bytes(b: &[u8]) -> Self {
+    GeometryShader{d: true, h: fresh(unsafe { LoadGeometryShaderFromMem(
+      b.as_ptr() as *const c_void, b.len() as i32) }) }
   }
   /// set shader
   pub fn set_shader(&self) {
-    unsafe { SetUseGeometryShader(self.h); }
+    unsafe { SetUseGeometryShader(self.handle()); }
   }
 }
@@ -0,0 +1,113 @@
+//! slice ext dx bridge for DxLib
+//!
+
+use crate::ext::graph::Graph;
+
+/// Insets (border widths of the source graph)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Insets {
+  /// left
+  pub l: i32,
+  /// top
+  pub t: i32,
+  /// right
+  pub r: i32,
+  /// bottom
+  pub b: i32
+}
+
+/// Insets
+impl Insets {
+  /// constructor
+  pub fn new(l: i32, t: i32, r: i32, b: i32) -> Self { Insets{l, t, r, b} }
+  /// all the same
+  pub fn all(w: i32) -> Self { Insets::new(w, w, w, w) }
+  /// from [l, t, r, b]
+  pub fn get(v: &[i32; 4]) -> Self { Insets::new(v[0], v[1], v[2], v[3]) }
+}
+
+/// SliceFill
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SliceFill {
+  /// extend the part to the area
+  Stretch,
+  /// repeat the part (the last one is cut)
+  Tile
+}
+
+/// SliceMode (fill of edges and centre)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SliceMode {
+  /// top bottom left right edges
+  pub edge: SliceFill,
+  /// centre
+  pub center: SliceFill
+}
+
+/// SliceMode
+impl SliceMode {
+  /// stretch all
+  pub const STRETCH: SliceMode =
+    SliceMode{edge: SliceFill::Stretch, center: SliceFill::Stretch};
+  /// tile all
+  pub const TILE: SliceMode =
+    SliceMode{edge: SliceFill::Tile, center: SliceFill::Tile};
+}
+
+/// split 0..n into a b (inset) and the rest (shrink a b when n is small)
+fn split(n: i32, a: i32, b: i32) -> [(i32, i32); 3] {
+  let (a, b) = if a + b > n && a + b > 0 {
+    let a = n * a / (a + b);
+    (a, n - a)
+  } else { (a, b) };
+  [(0, a), (a, n - a - b), (n - b, b)]
+}
+
+/// pieces along one axis (dst pos, dst len, src pos, src len)
+fn span(fill: SliceFill, d: (i32, i32), s: (i32, i32))
+  -> Vec<(i32, i32, i32, i32)> {
+  if d.1 <= 0 || s.1 <= 0 { return vec![] }
+  match fill {
+  SliceFill::Stretch => vec![(d.0, d.1, s.0, s.1)],
+  SliceFill::Tile => (0..(d.1 + s.1 - 1) / s.1).map(|i| {
+    let w = s.1.min(d.1 - i * s.1);
+    (d.0 + i * s.1, w, s.0, w)
+  }).collect()
+  }
+}
+
+/// pieces of nine slice ([x y w h] on dst, [x y w h] on src)
+/// - src: w h of the source graph, dst: x y w h
+pub fn nine_slice_pieces(src: (i32, i32), dst: [i32; 4], ins: &Insets,
+  mode: SliceMode) -> Vec<([i32; 4], [i32; 4])> {
+  let [x, y, w, h] = dst;
+  let (sx, sy) = (split(src.0, ins.l, ins.r), split(src.1, ins.t, ins.b));
+  let (dx, dy) = (split(w, ins.l, ins.r), split(h, ins.t, ins.b));
+  let mut v = vec![];
+  for j in 0..3 {
+    for i in 0..3 {
+      let f = |k| if k == 1 { if i == 1 && j == 1 { mode.center }
+        else { mode.edge } } else { SliceFill::Stretch }; // corner as is
+      let cs = span(f(i), (x + dx[i].0, dx[i].1), sx[i]);
+      let rs = span(f(j), (y + dy[j].0, dy[j].1), sy[j]);
+      for r in rs.iter() {
+        for c in cs.iter() {
+          v.push(([c.0, r.0, c.1, r.1], [c.2, r.2, c.3, r.3]));
+        }
+      }
+    }
+  }
+  v
+}
+
+/// nine slice for Graph
+impl Graph {
+  /// draw the graph as a panel (rect: x y w h, corners are not scaled)
+  pub fn draw_nine_slice(&self, rect: [i32; 4], insets: &Insets,
+    mode: SliceMode, trans: i32) {
+    for (d, s) in nine_slice_pieces(self.get_size(), rect, insets, mode) {
+      self.draw_rect_extend(d[0], d[1], d[0] + d[2], d[1] + d[3],
+        s[0], s[1], s[2], s[3], trans);
+    }
+  }
+}
@@ -0,0 +1,198 @@
+//! soft ext dx bridge for DxLib
+//!
+
+use crate::{dx::*, ext::tdx::*};
+use crate::ext::graph::Graph;
+
+/// bytes of w * h r g b a (None: negative or overflow)
+pub fn rgba_len(w: i32, h: i32) -> Option<usize> {
+  if w < 0 || h < 0 { return None }
+  (w as usize).checked_mul(h as usize)?.checked_mul(4)
+}
+
+/// SoftImage (CPU side ARGB8 image)
+/// - only one of the copies is writable and has the pixel slices
+/// - pixels are written through &mut self of it (-1 or None on the others)
+pub struct SoftImage {
+  /// to be disposed
+  pub d: bool,
+  /// handle
+  pub h: i32,
+  /// writable
+  w: bool
+}
+
+/// Tr for SoftImage
+impl Tr for SoftImage {
+  /// as soft image (read only)
+  fn as_soft_image(&self) -> SoftImage {
+    SoftImage{d: false, h: self.h, w: false}
+  }
+
+  /// handle
+  fn handle(&self) -> i32 { self.h }
+  /// dispose
+  fn dispose(&mut self) {
+    if self.d && self.h != 0 {
+      unsafe { DeleteSoftImage(self.h); }
+      self.h = 0;
+    }
+  }
+}
+
+/// Drop for SoftImage
+impl Drop for SoftImage {
+  /// drop
+  fn drop(&mut self) { self.dispose(); }
+}
+
+/// SoftImage
+impl SoftImage {
+  /// load from file (always converted to ARGB8)
+  pub fn load(n: &String) -> Self {
+    SoftImage{d: true, h: unsafe { LoadARGB8ColorSoftImage(n.as_ptr()) },
+      w: true}
+  }
+  /// empty ARGB8 image
+  pub fn make(xsz: i32, ysz: i32) -> Self {
+    SoftImage{d: true, h: unsafe { MakeARGB8ColorSoftImage(xsz, ysz) },
+      w: true}
+  }
+  /// move the write permission to a not disposed copy (self is read only)
+  pub fn writer(&mut self) -> Self {
+    let w = self.w;
+    self.w = false;
+    SoftImage{d: false, h: self.h, w}
+  }
+  /// writable (false: read only copy)
+  pub fn writable(&self) -> bool { self.w }
+  /// get size
+  pub fn get_size(&self) -> (i32, i32) {
+    let mut w = 0i32;
+    let mut h = 0i32;
+    unsafe { GetSoftImageSize(self.h, &mut w as *mut i32, &mut h as *mut i32); }
+    (w, h)
+  }
+  /// bytes per line
+  pub fn pitch(&self) -> i32 {
+    unsafe { GetPitchSoftImage(self.h) }
+  }
+  /// get pixel
+  pub fn get_pixel(&self, x: i32, y: i32) -> COLOR_U8 {
+    let (mut r, mut g, mut b, mut a) = (0i32, 0i32, 0i32, 0i32);
+    unsafe { GetPixelSoftImage(self.h, x, y, &mut r as *mut i32,
+      &mut g as *mut i32, &mut b as *mut i32, &mut a as *mut i32); }
+    COLOR_U8::new(b as u8, g as u8, r as u8, a as u8)
+  }
+  /// draw pixel
+  pub fn draw_pixel(&mut self, x: i32, y: i32, c: &COLOR_U8) -> i32 {
+    if !self.w { return -1 }
+    unsafe { DrawPixelSoftImage(self.h, x, y,
+      c.r as i32, c.g as i32, c.b as i32, c.a as i32) }
+  }
+  /// fill
+  pub fn fill(&mut self, c: &COLOR_U8) -> i32 {
+    if !self.w { return -1 }
+    unsafe { FillSoftImage(self.h,
+      c.r as i32, c.g as i32, c.b as i32, c.a as i32) }
+  }
+  /// clear rect (0 0 0 0)
+  pub fn clear_rect(&mut self, x: i32, y: i32, w: i32, h: i32) -> i32 {
+    if !self.w { return -1 }
+    unsafe { ClearRectSoftImage(self.h, x, y, w, h) }
+  }
+  /// copy rect of src to (dstx, dsty) of self
+  pub fn blt(&mut self, src: &SoftImage, srcx: i32, srcy: i32, w: i32, h: i32,
+    dstx: i32, dsty: i32) -> i32 {
+    if !self.w { return -1 }
+    unsafe { BltSoftImage(srcx, srcy, w, h, src.h, dstx, dsty, self.h) }
+  }
+  /// reverse LR
+  pub fn reverse_h(&mut self) -> i32 {
+    if !self.w { return -1 }
+    unsafe { ReverseSoftImageH(self.h) }
+  }
+  /// reverse TB
+  pub fn reverse_v(&mut self) -> i32 {
+    if !self.w { return -1 }
+    unsafe { ReverseSoftImageV(self.h) }
+  }
+  /// reverse LR and TB
+  pub fn reverse(&mut self) -> i32 {
+    if !self.w { return -1 }
+    unsafe { ReverseSoftImage(self.h) }
+  }
+  /// raw bytes (pitch * height, empty on read only copies)
+  pub fn as_bytes(&self) -> &[u8] {
+    if !self.w { return &[] }
+    let (_, h) = self.get_size();
+    let p = unsafe { GetImageAddressSoftImage(self.h) } as *const u8;
+    if p.is_null() { return &[] }
+    let l = self.pitch().max(0) as usize * h.max(0) as usize;
+    unsafe { std::slice::from_raw_parts(p, l) }
+  }
+  /// raw bytes mut (pitch * height, empty on read only copies)
+  pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+    if !self.w { return &mut [] }
+    let (_, h) = self.get_size();
+    let p = unsafe { GetImageAddressSoftImage(self.h) } as *mut u8;
+    if p.is_null() { return &mut [] }
+    let l = self.pitch().max(0) as usize * h.max(0) as usize;
+    unsafe { std::slice::from_raw_parts_mut(p, l) }
+  }
+  /// byte range of line y in as_bytes (None: out of range)
+  fn row_range(&self, y: i32) -> Option<std::ops::Range<usize>> {
+    let (w, h) = self.get_size();
+    if y < 0 || y >= h || w < 0 { return None }
+    let o = (y as usize).checked_mul(self.pitch().max(0) as usize)?;
+    Some(o..o.checked_add(w as usize * 4)?)
+  }
+  /// pixels of line y (b g r a, None: out of range or read only copy)
+  pub fn row(&self, y: i32) -> Option<&[COLOR_U8]> {
+    let b = self.as_bytes().get(self.row_range(y)?)?;
+    Some(unsafe { std::slice::from_raw_parts(b.as_ptr() as *const COLOR_U8,
+      b.len() / 4) })
+  }
+  /// pixels of line y mut (b g r a, None: out of range or read only copy)
+  pub fn row_mut(&mut self, y: i32) -> Option<&mut [COLOR_U8]> {
+    let r = self.row_range(y)?;
+    let b = self.as_bytes_mut().get_mut(r)?;
+    Some(unsafe { std::slice::from_raw_parts_mut(
+      b.as_mut_ptr() as *mut COLOR_U8, b.len() / 4) })
+  }
+  /// copy to r g b a bytes (w * h * 4, empty on read only copies)
+  pub fn to_rgba(&self) -> Vec<u8> {
+    let (_, h) = self.get_size();
+    (0..h).map_while(|y| self.row(y)).flat_map(|r| r.iter().flat_map(|c|
+      [c.r, c.g, c.b, c.a])).collect()
+  }
+  /// copy from r g b a bytes (w * h * 4, -1: size differs or read only)
+  pub fn write_rgba(&mut self, rgba: &[u8]) -> i32 {
+    let (w, h) = self.get_size();
+    if !self.w || rgba_len(w, h) != Some(rgba.len()) { return -1 }
+    if w == 0 { return 0 }
+    for (y, s) in (0..h).zip(rgba.chunks_exact(w as usize * 4)) {
+      let Some(r) = self.row_mut(y) else { return -1 };
+      for (d, p) in r.iter_mut().zip(s.chunks_exact(4)) {
+        *d = COLOR_U8::new(p[2], p[1], p[0], p[3]);
+      }
+    }
+    0
+  }
+  /// save png (compression_level 0-9 or -1: default)
+  pub fn save_png(&self, n: &String, compression_level: i32) -> i32 {
+    unsafe { SaveSoftImageToPng(n.as_ptr(), self.h, compression_level) }
+  }
+  /// save bmp
+  pub fn save_bmp(&self, n: &String) -> i32 {
+    unsafe { SaveSoftImageToBmp(n.as_ptr(), self.h) }
+  }
+  /// save jpeg (quality 0-100)
+  pub fn save_jpeg(&self, n: &String, quality: i32, sample2x1: i32) -> i32 {
+    unsafe { SaveSoftImageToJpeg(n.as_ptr(), self.h, quality, sample2x1) }
+  }
+  /// new graph from this image
+  pub fn to_graph(&self) -> Graph {
+    Graph{d: true, h: unsafe { CreateGraphFromSoftImage(self.h) } }
+  }
+}
@@ -1,6 +1,8 @@
 //! sound ext dx bridge for DxLib
 //!
 
+use std::ffi::c_void;
+
 use crate::{dx::*, ext::tdx::*};
 
 /// Sound
@@ -39,6 +41,11 @@ impl Sound {
   pub fn load_mem(n: &String) -> Self {
     Sound{d: true, h: unsafe { LoadSoundMem(n.as_ptr()) } }
   }
+  /// from wav ogg etc file image (include_bytes! etc, kept for streaming)
+  pub fn from_bytes(b: &'static [u8]) -> Self {
+    Sound{d: true, h: unsafe {
+      LoadSoundMemByMemImage(b.as_ptr() as *const c_void, b.len(), 3, -1) } }
+  }
   /// volume
   pub fn volume(&self, v: i32) { unsafe { ChangeVolumeSoundMem(v, self.h); } }
   /// stop
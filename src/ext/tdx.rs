@@ -10,19 +10,31 @@ use std::collections::HashMap;
 use crate::{dx::*, ext::*};
 use crate::ext::music::Music;
 use crate::ext::sound::Sound;
-use crate::ext::graph::{Screen, Graph};
+use crate::ext::movie::Movie;
+use crate::ext::graph::{Screen, Graph, Mask};
+use crate::ext::soft::{SoftImage, rgba_len};
 use crate::ext::shader::{VertexShader, PixelShader, GeometryShader};
+use crate::ext::shader::swap_shader;
+use crate::ext::reload::{HotReload, ReloadKind, Watch};
 use crate::ext::shader::ConstantBuffer;
+use crate::ext::cbuffer::CbLayout;
 use crate::ext::light::Light;
 use crate::ext::font::Font;
+use crate::ext::atlas::{AtlasDesc, SpriteAtlas};
+use crate::ext::tilemap::{TileMapDesc, TileMap};
+use crate::ext::mrt::ScreenFormat;
+use crate::ext::image::{ImageInfo, DivGrid};
 
 pub type RcTr = Arc<RefCell<Box<dyn Tr>>>;
 
 pub trait Tr {
   fn as_music(&self) -> Music { panic!("music") }
   fn as_sound(&self) -> Sound { panic!("sound") }
+  fn as_movie(&self) -> Movie { panic!("movie") }
   fn as_screen(&self) -> Screen { panic!("screen") }
   fn as_graph(&self) -> Graph { panic!("graph") }
+  fn as_soft_image(&self) -> SoftImage { panic!("soft_image") }
+  fn as_mask(&self) -> Mask { panic!("mask") }
   fn as_vertex_shader(&self) -> VertexShader { panic!("vertex_shader") }
   fn as_pixel_shader(&self) -> PixelShader { panic!("pixel_shader") }
   fn as_geometry_shader(&self) -> GeometryShader { panic!("geometry_shader") }
@@ -36,8 +48,10 @@ pub trait Tr {
 
 /// for DX11 and DX9
 pub trait Ts: Tr {
+  /// DX_SHADERTYPE_VERTEX DX_SHADERTYPE_PIXEL etc
+  fn shader_type(&self) -> i32;
   /// for DX11
-  fn set_const(&self, cb: &ConstantBuffer) -> i32;
+  fn set_const<T>(&self, cb: &ConstantBuffer<T>) -> i32;
   /// must end 0 (for DX9)
   fn get_const_default_param_f_to_shader(&self, n: &str) -> *const FLOAT4 {
     unsafe { GetConstDefaultParamFToShader(n.as_ptr(), self.handle()) }
@@ -65,13 +79,15 @@ pub trait Ts: Tr {
 }
 
 pub struct Tdx {
-  pub tbl: HashMap<i32, RcTr>
+  pub tbl: HashMap<i32, RcTr>,
+  /// watched files (reload_changed)
+  pub hot: HotReload
 }
 
 impl Tdx {
   pub fn new() -> Result<Self, Box<dyn Error>> {
     if unsafe { DxLib_Init() } == -1 { return Err("Cannot init DxLib".into()) }
-    Ok(Tdx{tbl: HashMap::new()})
+    Ok(Tdx{tbl: HashMap::new(), hot: HotReload::default()})
   }
 
   pub fn unreg(&mut self, o: Box<dyn Tr>) {
@@ -97,20 +113,50 @@ impl Tdx {
     .borrow().as_sound()
   }
 
+  /// from midi file image (include_bytes! etc)
+  pub fn load_music_mem_from_bytes(&mut self, b: &'static [u8]) -> Music {
+    self.reg(Box::new(Music::from_bytes(b)))
+    .borrow().as_music()
+  }
+
+  /// from wav ogg etc file image (include_bytes! etc)
+  pub fn load_sound_mem_from_bytes(&mut self, b: &'static [u8]) -> Sound {
+    self.reg(Box::new(Sound::from_bytes(b)))
+    .borrow().as_sound()
+  }
+
+  /// full_color: default TRUE
+  pub fn open_movie(&mut self, n: &String, full_color: i32) -> Movie {
+    self.reg(Box::new(Movie::open(n, full_color)))
+    .borrow().as_movie()
+  }
+
   pub fn make_screen(&mut self, xsz: i32, ysz: i32, trans: i32) -> Screen {
     self.reg(Box::new(Screen::make(xsz, ysz, trans)))
     .borrow().as_screen()
   }
 
-  /// inner change draw screen
-  pub fn make_graph_color(&mut self, xsz: i32, ysz: i32, c: u32,
-    trans: i32, use_client_flag: i32, not_use_3d_flag: i32) -> Graph {
-    let screen = self.make_screen(xsz, ysz, trans);
-    screen.set_draw();
-    draw_box(0, 0, xsz, ysz, c, TRUE);
-    let g = self.get_graph(0, 0, xsz, ysz, use_client_flag, not_use_3d_flag);
-    self.unreg(Box::new(screen));
-    g
+  /// screen of the channels bits float format (for Mrt etc)
+  pub fn make_screen_format(&mut self, xsz: i32, ysz: i32, trans: i32,
+    fmt: &ScreenFormat) -> Screen {
+    self.reg(Box::new(fmt.make_screen(xsz, ysz, trans)))
+    .borrow().as_screen()
+  }
+
+  /// filled by c (alpha 255) without changing draw screen
+  pub fn make_graph_color(&mut self, xsz: i32, ysz: i32, c: u32) ->
+    Result<Graph, Box<dyn Error>> {
+    let l = rgba_len(xsz, ysz)
+      .ok_or_else(|| format!("make_graph_color: size {}x{}", xsz, ysz))?;
+    let u = COLOR_U8::from_u32(c);
+    self.make_graph_from_rgba(xsz, ysz, &[u.r, u.g, u.b, 255].repeat(l / 4))
+  }
+
+  /// from r g b a bytes (w * h * 4) without changing draw screen
+  pub fn make_graph_from_rgba(&mut self, w: i32, h: i32, rgba: &[u8])
+    -> Result<Graph, Box<dyn Error>> {
+    let g = Graph::from_rgba(w, h, rgba)?;
+    Ok(self.reg(Box::new(g)).borrow().as_graph())
   }
 
   /// inner change draw screen
@@ -147,16 +193,145 @@ impl Tdx {
     .borrow().as_graph()
   }
 
+  /// from png bmp jpeg etc file image (include_bytes! etc)
+  pub fn load_graph_from_bytes(&mut self, b: &[u8]) -> Graph {
+    self.reg(Box::new(Graph::from_bytes(b)))
+    .borrow().as_graph()
+  }
+
+  pub fn make_mask(&mut self, xsz: i32, ysz: i32) -> Mask {
+    self.reg(Box::new(Mask::make(xsz, ysz)))
+    .borrow().as_mask()
+  }
+
+  pub fn load_mask(&mut self, n: &String) -> Mask {
+    self.reg(Box::new(Mask::load(n)))
+    .borrow().as_mask()
+  }
+
+  /// always ARGB8 (returns the writable copy)
+  pub fn load_soft_image(&mut self, n: &String) -> SoftImage {
+    let mut si = SoftImage::load(n);
+    let w = si.writer();
+    self.reg(Box::new(si));
+    w
+  }
+
+  /// ARGB8 (returns the writable copy)
+  pub fn make_soft_image(&mut self, xsz: i32, ysz: i32) -> SoftImage {
+    let mut si = SoftImage::make(xsz, ysz);
+    let w = si.writer();
+    self.reg(Box::new(si));
+    w
+  }
+
+  pub fn create_graph_from_soft_image(&mut self, si: &SoftImage) -> Graph {
+    self.reg(Box::new(si.to_graph()))
+    .borrow().as_graph()
+  }
+
+  /// grid is checked with the image header before loading
+  /// - only counts are checked when the header is not known (TGA DDS etc)
+  /// - broken headers of PNG BMP JPEG are errors
+  /// - xstride ystride: distance between cells (0: xsz ysz)
   pub fn load_div_graph(&mut self, n: &String, allnum: i32,
     xnum: i32, ynum: i32, xsz: i32, ysz: i32,
-    not_use_3d_flag: i32, xstride: i32, ystride: i32) -> Vec<Graph> {
+    not_use_3d_flag: i32, xstride: i32, ystride: i32) ->
+    Result<Vec<Graph>, Box<dyn Error>> {
+    let g = DivGrid{allnum, xnum, ynum, xsz, ysz, xstride, ystride};
+    let (w, h) = ImageInfo::probe(n)?.map_or((i32::MAX, i32::MAX),
+      |img| (img.w, img.h)); // leave unknown formats to DxLib
+    g.check(w, h)
+      .map_err(|e| format!("{}: {}", n.trim_end_matches('\0'), e))?;
     let mut handle_buf = vec![0i32; allnum as usize];
-    unsafe { LoadDivGraph(n.as_ptr(), allnum,
-      xnum, ynum, xsz, ysz, &mut handle_buf[0] as *mut i32,
-      not_use_3d_flag, xstride, ystride); }
-    handle_buf.into_iter().map(|h|
+    let r = unsafe { LoadDivGraph(n.as_ptr(), allnum,
+      xnum, ynum, xsz, ysz, handle_buf.as_mut_ptr(),
+      not_use_3d_flag, xstride, ystride) };
+    if r == -1 {
+      return Err(format!("LoadDivGraph: {}", n.trim_end_matches('\0')).into())
+    }
+    Ok(handle_buf.into_iter().map(|h|
       self.reg(Box::new(Graph{d: true, h}))
-      .borrow().as_graph()).collect()
+      .borrow().as_graph()).collect())
+  }
+
+  /// all cells of cell_w x cell_h (counts from the image size)
+  /// - PNG BMP JPEG only (see ImageInfo)
+  pub fn load_div_graph_auto(&mut self, n: &String,
+    cell_w: i32, cell_h: i32) -> Result<Vec<Graph>, Box<dyn Error>> {
+    let img = ImageInfo::load(n)?;
+    let g = DivGrid::auto(img.w, img.h, cell_w, cell_h)
+      .map_err(|e| format!("{}: {}", n.trim_end_matches('\0'), e))?;
+    self.load_div_graph(n, g.allnum, g.xnum, g.ynum, g.xsz, g.ysz,
+      FALSE, 0, 0)
+  }
+
+  /// sub rect shares the texture of src
+  pub fn derivation_graph(&mut self, src: &Graph,
+    srcx: i32, srcy: i32, w: i32, h: i32) -> Graph {
+    self.reg(Box::new(Graph::derivation(src, srcx, srcy, w, h)))
+    .borrow().as_graph()
+  }
+
+  /// descriptor .toml grid or TexturePacker .json (image relative to it)
+  pub fn load_sprite_atlas(&mut self, n: &String) ->
+    Result<SpriteAtlas, Box<dyn Error>> {
+    let desc = AtlasDesc::load(n)?;
+    let image = desc.image.as_ref().ok_or("atlas: no image")?;
+    let dir = std::path::Path::new(n.trim_end_matches('\0'))
+      .parent().unwrap_or(std::path::Path::new(""));
+    let tp = format!("{}\0", dir.join(image).to_str().ok_or("atlas: path")?);
+    let tex = self.load_graph(&tp);
+    if tex.handle() == -1 { return Err(format!("atlas: load {}", image).into()) }
+    Ok(self.make_sprite_atlas(tex, desc))
+  }
+
+  /// sub handles from the loaded texture
+  pub fn make_sprite_atlas(&mut self, tex: Graph, desc: AtlasDesc) ->
+    SpriteAtlas {
+    let graphs = desc.frames.iter().map(|f| {
+      let [x, y, w, h] = f.sheet_rect();
+      self.derivation_graph(&tex, x, y, w, h)
+    }).collect();
+    let names = desc.frames.iter().enumerate().map(|(i, f)|
+      (f.name.clone(), i)).collect();
+    SpriteAtlas{tex, frames: desc.frames, graphs, names}
+  }
+
+  /// Tiled .tmx or .tmj .json (tileset images relative to it)
+  pub fn load_tile_map(&mut self, n: &String) ->
+    Result<TileMap, Box<dyn Error>> {
+    let desc = TileMapDesc::load(n)?;
+    let dir = std::path::Path::new(n.trim_end_matches('\0'))
+      .parent().unwrap_or(std::path::Path::new(""));
+    let texs = desc.tilesets.iter().map(|ts| {
+      let Some(image) = ts.image.as_ref() else {
+        return Ok(Graph{d: false, h: -1}) // image collection
+      };
+      let tp = dir.join(image);
+      let tp = format!("{}\0", tp.to_str().ok_or("tilemap: path")?);
+      let tex = self.load_graph(&tp);
+      if tex.handle() == -1 {
+        return Err(format!("tilemap: load {}", image).into())
+      }
+      Ok(tex)
+    }).collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    Ok(self.make_tile_map(desc, texs))
+  }
+
+  /// sub handles from the loaded textures (one for each tileset)
+  /// - tiles out of the texture are not made
+  pub fn make_tile_map(&mut self, desc: TileMapDesc, texs: Vec<Graph>) ->
+    TileMap {
+    let tiles = desc.tilesets.iter().zip(texs.iter()).map(|(ts, tex)| {
+      if tex.handle() == -1 { return vec![] }
+      let (w, h) = tex.get_size();
+      (0..ts.count.min(ts.capacity(w, h)).max(0) as u32).map(|id| {
+        let [x, y, w, h] = ts.tile_rect(id);
+        self.derivation_graph(tex, x, y, w, h)
+      }).collect()
+    }).collect();
+    TileMap{desc, texs, tiles}
   }
 
   pub fn load_vertex_shader(&mut self, n: &String) -> VertexShader {
@@ -174,12 +349,118 @@ impl Tdx {
     .borrow().as_geometry_shader()
   }
 
+  /// from .vso file image (include_bytes! etc)
+  pub fn load_vertex_shader_from_bytes(&mut self, b: &[u8]) -> VertexShader {
+    self.reg(Box::new(VertexShader::from_bytes(b)))
+    .borrow().as_vertex_shader()
+  }
+
+  /// from .pso file image (include_bytes! etc)
+  pub fn load_pixel_shader_from_bytes(&mut self, b: &[u8]) -> PixelShader {
+    self.reg(Box::new(PixelShader::from_bytes(b)))
+    .borrow().as_pixel_shader()
+  }
+
+  /// from .gso file image (include_bytes! etc)
+  pub fn load_geometry_shader_from_bytes(&mut self, b: &[u8])
+    -> GeometryShader {
+    self.reg(Box::new(GeometryShader::from_bytes(b)))
+    .borrow().as_geometry_shader()
+  }
+
+  /// reload by reload_changed when the file n of vs is changed
+  pub fn watch_vertex_shader(&mut self, n: &String, vs: &VertexShader) {
+    self.hot.watch(n, ReloadKind::Vertex, vs.handle());
+  }
+
+  /// reload by reload_changed when the file n of ps is changed
+  pub fn watch_pixel_shader(&mut self, n: &String, ps: &PixelShader) {
+    self.hot.watch(n, ReloadKind::Pixel, ps.handle());
+  }
+
+  /// reload by reload_changed when the file n of gs is changed
+  pub fn watch_geometry_shader(&mut self, n: &String, gs: &GeometryShader) {
+    self.hot.watch(n, ReloadKind::Geometry, gs.handle());
+  }
+
+  /// reload by reload_changed when the file n of g is changed
+  pub fn watch_graph(&mut self, n: &String, g: &Graph) {
+    self.hot.watch(n, ReloadKind::Graph, g.handle());
+  }
+
+  /// reload the watched files changed (call in the main loop)
+  /// - returns the number of reloaded files
+  /// - a failed reload is logged and the old handle is kept
+  pub fn reload_changed(&mut self) -> usize {
+    let mut c = 0;
+    for i in self.hot.poll() {
+      let w = self.hot.watches[i].clone();
+      match self.reload(&w) {
+      Ok(h) => { self.hot.watches[i].h = h; c += 1; },
+      Err(e) => eprintln!("reload: {}", e)
+      }
+    }
+    c
+  }
+
+  /// reload the file and swap the registry entry (returns the handle)
+  fn reload(&mut self, w: &Watch) -> Result<i32, Box<dyn Error>> {
+    let f = w.n.trim_end_matches('\0');
+    let rc = self.tbl.get(&w.h).cloned()
+      .ok_or_else(|| format!("{}: {:08x} is not registered", f, w.h))?;
+    let h = unsafe { match w.kind {
+    ReloadKind::Graph => {
+      if ReloadGraph(w.n.as_ptr(), w.h, FALSE) == -1 {
+        return Err(format!("{}: ReloadGraph {:08x}", f, w.h).into())
+      }
+      return Ok(w.h) // same handle
+    },
+    ReloadKind::Vertex => LoadVertexShader(w.n.as_ptr()),
+    ReloadKind::Pixel => LoadPixelShader(w.n.as_ptr()),
+    ReloadKind::Geometry => LoadGeometryShader(w.n.as_ptr())
+    } };
+    if h == -1 {
+      return Err(format!("{}: load failed (keep {:08x})", f, w.h).into())
+    }
+    let o: Box<dyn Tr> = match w.kind {
+    ReloadKind::Vertex => Box::new(VertexShader{d: true, h}),
+    ReloadKind::Pixel => Box::new(PixelShader{d: true, h}),
+    _ => Box::new(GeometryShader{d: true, h})
+    };
+    swap_shader(w.h, h); // before dispose (copies refer to h)
+    rc.borrow_mut().dispose(); // delete the old shader
+    *rc.borrow_mut() = o;
+    self.tbl.remove(&w.h);
+    self.tbl.insert(h, rc);
+    Ok(h)
+  }
+
   /// for DX11
   /// - n: number of FLOAT4 (alloc n * 4 * sizeof f32)
   /// - s: slot
+  /// - returns the writable copy (the registered one is read only)
   pub fn create_constant_buffer(&mut self, n: i32, s: i32) -> ConstantBuffer {
-    self.reg(Box::new(ConstantBuffer::create(n, s)))
-    .borrow().as_constant_buffer()
+    let mut cb = ConstantBuffer::create(n, s);
+    let w = cb.writer();
+    self.reg(Box::new(cb));
+    w
+  }
+
+  /// for DX11 (T declared by cbuffer!)
+  /// - s: slot
+  /// - returns the writable copy (the registered one is read only)
+  pub fn create_typed_constant_buffer<T: CbLayout>(&mut self, s: i32)
+    -> ConstantBuffer<T> {
+    let mut cb = ConstantBuffer::<T>::create_typed(s);
+    let w = cb.writer();
+    self.reg(Box::new(cb));
+    w
+  }
+
+  /// (move) light of p.light_type
+  pub fn create_light(&mut self, p: &LIGHTPARAM)
+    -> Result<Light, Box<dyn Error>> {
+    Ok(self.reg(Box::new(Light::from_param(p)?)).borrow().as_light())
   }
 
   /// (move)
@@ -227,6 +508,12 @@ impl Tdx {
     self.reg(Box::new(Font::load_data(n)))
     .borrow().as_font()
   }
+
+  /// from dft file image (include_bytes! etc)
+  pub fn load_font_from_bytes(&mut self, b: &[u8]) -> Font {
+    self.reg(Box::new(Font::from_bytes(b)))
+    .borrow().as_font()
+  }
 }
 
 impl Drop for Tdx {
@@ -325,6 +612,28 @@ pub fn process_music_mem() -> i32 {
   unsafe { ProcessMusicMem() }
 }
 
+pub fn init_mask() -> i32 {
+  unsafe { InitMask() }
+}
+
+/// size of the draw screen (after set_draw_screen)
+pub fn create_mask_screen() -> i32 {
+  unsafe { CreateMaskScreen() }
+}
+
+pub fn delete_mask_screen() -> i32 {
+  unsafe { DeleteMaskScreen() }
+}
+
+/// 0: transparent all 1: masked all
+pub fn fill_mask_screen(flg: i32) -> i32 {
+  unsafe { FillMaskScreen(flg) }
+}
+
+pub fn init_soft_image() -> i32 {
+  unsafe { InitSoftImage() }
+}
+
 pub fn init_shader() -> i32 {
   unsafe { InitShader() }
 }
@@ -521,6 +830,34 @@ pub fn draw_polygon_3d_to_shader_or_wire(va: &Vec<VERTEX3DSHADER>,
   }
 }
 
+pub fn draw_polygon_2d(va: &Vec<VERTEX2D>, gh: i32, trans: i32) -> i32 {
+  unsafe {
+    DrawPolygon2D(&va[0] as *const VERTEX2D, va.len() as i32 / 3, gh, trans)
+  }
+}
+
+pub fn draw_primitive_2d(va: &Vec<VERTEX2D>, prim: i32,
+  gh: i32, trans: i32) -> i32 {
+  unsafe {
+    DrawPrimitive2D(&va[0] as *const VERTEX2D, va.len() as i32,
+      prim, gh, trans)
+  }
+}
+
+pub fn draw_polygon_2d_to_shader(va: &Vec<VERTEX2DSHADER>) -> i32 {
+  unsafe {
+    DrawPolygon2DToShader(&va[0] as *const VERTEX2DSHADER, va.len() as i32 / 3)
+  }
+}
+
+pub fn draw_primitive_2d_to_shader(va: &Vec<VERTEX2DSHADER>,
+  prim: i32) -> i32 {
+  unsafe {
+    DrawPrimitive2DToShader(&va[0] as *const VERTEX2DSHADER,
+      va.len() as i32, prim)
+  }
+}
+
 pub fn draw_polygon_3d_to_shader(va: &Vec<VERTEX3DSHADER>) -> i32 {
   unsafe {
     DrawPolygon3DToShader(&va[0] as *const VERTEX3DSHADER, va.len() as i32 / 3)
@@ -0,0 +1,654 @@
+//! tilemap ext dx bridge for DxLib
+//!
+//! Tiled map editor maps (orthogonal, not infinite)
+//!
+//! - TMX (`.tmx` XML) or JSON (`.tmj` `.json`)
+//! - tile layer data: csv, base64 (not compressed), JSON array, `<tile gid>`
+//! - external tilesets (`.tsx` `.tsj` `.json`) are resolved by load
+//! - group layers are flattened in drawing order
+//!
+
+use std::error::Error;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::{dx::*, ext::json::Json, ext::xml::Xml};
+use crate::ext::graph::Graph;
+
+/// flipped horizontally
+pub const FLIP_H: u32 = 0x80000000;
+/// flipped vertically
+pub const FLIP_V: u32 = 0x40000000;
+/// flipped diagonally (swap x y)
+pub const FLIP_D: u32 = 0x20000000;
+/// all flag bits (includes hexagonal 120 degrees rotation)
+const FLIP_MASK: u32 = 0xf0000000;
+
+/// Props (custom properties, bool int float as Json::Bool Json::Num)
+pub type Props = HashMap<String, Json>;
+
+/// Tile (global tile id and flip flags)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Tile {
+  /// global id (0: empty)
+  pub gid: u32,
+  /// flipped horizontally
+  pub flip_h: bool,
+  /// flipped vertically
+  pub flip_v: bool,
+  /// flipped diagonally
+  pub flip_d: bool
+}
+
+/// Tile
+impl Tile {
+  /// from raw gid with flags
+  pub fn from_raw(r: u32) -> Self {
+    Tile{gid: r & !FLIP_MASK,
+      flip_h: r & FLIP_H != 0, flip_v: r & FLIP_V != 0, flip_d: r & FLIP_D != 0}
+  }
+  /// empty
+  pub fn is_empty(&self) -> bool { self.gid == 0 }
+  /// flipped
+  pub fn flipped(&self) -> bool { self.flip_h || self.flip_v || self.flip_d }
+  /// angle reversex reversey for draw_rota (DxLib reverses then rotates)
+  /// - Tiled flips diagonally (x y swap) then horizontally then vertically
+  pub fn rota(&self) -> (f64, bool, bool) {
+    if self.flip_d {
+      (std::f64::consts::FRAC_PI_2, self.flip_v, !self.flip_h)
+    } else {
+      (0.0, self.flip_h, self.flip_v)
+    }
+  }
+}
+
+/// Tileset
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Tileset {
+  /// first global id
+  pub firstgid: u32,
+  /// name
+  pub name: String,
+  /// tile w
+  pub tile_w: i32,
+  /// tile h
+  pub tile_h: i32,
+  /// columns on the image
+  pub columns: i32,
+  /// number of tiles
+  pub count: i32,
+  /// margin around the image
+  pub margin: i32,
+  /// spacing between tiles
+  pub spacing: i32,
+  /// image file name (relative to the map)
+  pub image: Option<String>,
+  /// external tileset file name (relative to the map, None when resolved)
+  pub source: Option<String>,
+  /// local id to properties
+  pub props: HashMap<u32, Props>
+}
+
+/// Tileset
+impl Tileset {
+  /// gid belongs to this tileset
+  pub fn contains(&self, gid: u32) -> bool {
+    gid.checked_sub(self.firstgid)
+      .is_some_and(|i| i < self.count.max(0) as u32)
+  }
+  /// number of tiles the image iw x ih holds by columns margin spacing
+  pub fn capacity(&self, iw: i32, ih: i32) -> i32 {
+    let fit = |n: i32, t: i32| ((n as i64 - self.margin as i64
+      + self.spacing as i64) / (t as i64 + self.spacing as i64).max(1)).max(0);
+    let c = (self.columns as i64).min(fit(iw, self.tile_w)).max(0);
+    (c * fit(ih, self.tile_h)).min(i32::MAX as i64) as i32
+  }
+  /// x y w h of local id on the image
+  pub fn tile_rect(&self, id: u32) -> [i32; 4] {
+    let (c, r) = (id as i32 % self.columns.max(1),
+      id as i32 / self.columns.max(1));
+    [self.margin + c * (self.tile_w + self.spacing),
+     self.margin + r * (self.tile_h + self.spacing), self.tile_w, self.tile_h]
+  }
+}
+
+/// TileLayer
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TileLayer {
+  /// name
+  pub name: String,
+  /// w (tiles)
+  pub w: i32,
+  /// h (tiles)
+  pub h: i32,
+  /// raw gids with flags (w * h)
+  pub data: Vec<u32>,
+  /// visible
+  pub visible: bool,
+  /// opacity 0.0-1.0
+  pub opacity: f32,
+  /// x y offset (pixels)
+  pub offset: [f32; 2],
+  /// properties
+  pub props: Props
+}
+
+/// TileLayer
+impl TileLayer {
+  /// tile at x y (empty when out of range)
+  pub fn get(&self, x: i32, y: i32) -> Tile {
+    if x < 0 || y < 0 || x >= self.w || y >= self.h { return Tile::default() }
+    Tile::from_raw(self.data[(y * self.w + x) as usize])
+  }
+}
+
+/// MapObject
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MapObject {
+  /// id
+  pub id: u32,
+  /// name
+  pub name: String,
+  /// type (class)
+  pub kind: String,
+  /// x (pixels, left bottom for tile objects)
+  pub x: f32,
+  /// y
+  pub y: f32,
+  /// w
+  pub w: f32,
+  /// h
+  pub h: f32,
+  /// rotation degrees clockwise
+  pub rotation: f32,
+  /// tile object
+  pub tile: Option<Tile>,
+  /// visible
+  pub visible: bool,
+  /// properties
+  pub props: Props
+}
+
+/// ObjectLayer
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ObjectLayer {
+  /// name
+  pub name: String,
+  /// objects
+  pub objects: Vec<MapObject>,
+  /// visible
+  pub visible: bool,
+  /// properties
+  pub props: Props
+}
+
+/// Layer
+#[derive(Debug, Clone, PartialEq)]
+pub enum Layer {
+  /// tile layer
+  Tile(TileLayer),
+  /// object layer
+  Object(ObjectLayer)
+}
+
+/// TileMapDesc (parsed map)
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TileMapDesc {
+  /// w (tiles)
+  pub w: i32,
+  /// h (tiles)
+  pub h: i32,
+  /// tile w (pixels)
+  pub tile_w: i32,
+  /// tile h (pixels)
+  pub tile_h: i32,
+  /// tilesets (ascending firstgid)
+  pub tilesets: Vec<Tileset>,
+  /// layers (drawing order)
+  pub layers: Vec<Layer>,
+  /// properties
+  pub props: Props
+}
+
+/// TileMapDesc
+impl TileMapDesc {
+  /// load .tmx or .tmj .json (and external tilesets)
+  pub fn load(n: &str) -> Result<Self, Box<dyn Error>> {
+    let n = n.trim_end_matches('\0');
+    let s = std::fs::read_to_string(n)?;
+    let mut m = match ext(n) {
+    Some("tmx") => TileMapDesc::from_tmx(&s)?,
+    _ => TileMapDesc::from_json(&s)?
+    };
+    let dir = Path::new(n).parent().unwrap_or(Path::new(""));
+    for ts in m.tilesets.iter_mut() {
+      let src = match ts.source.take() { None => continue, Some(s) => s };
+      let p = dir.join(&src);
+      let s = std::fs::read_to_string(&p)
+        .map_err(|e| format!("tilemap: tileset {}: {}", src, e))?;
+      let mut t = match ext(&src) {
+      Some("tsx") => tileset_from_xml(&Xml::parse(&s)?, ts.firstgid)?,
+      _ => tileset_from_json(&Json::parse(&s)?, ts.firstgid)?
+      };
+      let sd = Path::new(&src).parent().unwrap_or(Path::new(""));
+      t.image = t.image.map(|i|
+        sd.join(i).to_string_lossy().replace('\\', "/"));
+      *ts = t;
+    }
+    Ok(m)
+  }
+  /// TMX (XML)
+  pub fn from_tmx(s: &str) -> Result<Self, Box<dyn Error>> {
+    let x = Xml::parse(s)?;
+    if x.name != "map" { return Err("tilemap: root is not map".into()) }
+    check_map(x.attr("orientation"), x.attr("infinite") == Some("1"))?;
+    let int = |k: &str| x.attr_as::<i32>(k)
+      .ok_or_else(|| format!("tilemap: map without {}", k));
+    let mut m = TileMapDesc{w: int("width")?, h: int("height")?,
+      tile_w: int("tilewidth")?, tile_h: int("tileheight")?,
+      props: props_from_xml(&x), ..TileMapDesc::default()};
+    check_size(&m)?;
+    for t in x.children_named("tileset") {
+      let firstgid = t.attr_as("firstgid").ok_or("tilemap: no firstgid")?;
+      m.tilesets.push(match t.attr("source") {
+      Some(src) => Tileset{firstgid, source: Some(src.to_string()),
+        ..Tileset::default()},
+      None => tileset_from_xml(t, firstgid)?
+      });
+    }
+    layers_from_xml(&x, &mut m.layers)?;
+    m.tilesets.sort_by_key(|t| t.firstgid);
+    Ok(m)
+  }
+  /// JSON
+  pub fn from_json(s: &str) -> Result<Self, Box<dyn Error>> {
+    let j = Json::parse(s)?;
+    check_map(j.get("orientation").and_then(|o| o.as_str()),
+      j.get("infinite").and_then(|i| i.as_bool()).unwrap_or(false))?;
+    let int = |k: &str| j.get(k).and_then(|v| v.as_i32())
+      .ok_or_else(|| format!("tilemap: map without {}", k));
+    let mut m = TileMapDesc{w: int("width")?, h: int("height")?,
+      tile_w: int("tilewidth")?, tile_h: int("tileheight")?,
+      props: props_from_json(&j), ..TileMapDesc::default()};
+    check_size(&m)?;
+    for t in j.get("tilesets").and_then(|t| t.as_arr()).unwrap_or(&vec![]) {
+      let firstgid = t.get("firstgid").and_then(|f| f.as_f64())
+        .ok_or("tilemap: no firstgid")? as u32;
+      m.tilesets.push(match t.get("source").and_then(|s| s.as_str()) {
+      Some(src) => Tileset{firstgid, source: Some(src.to_string()),
+        ..Tileset::default()},
+      None => tileset_from_json(t, firstgid)?
+      });
+    }
+    layers_from_json(&j, &mut m.layers)?;
+    m.tilesets.sort_by_key(|t| t.firstgid);
+    Ok(m)
+  }
+  /// tileset index and local id of gid
+  pub fn tileset(&self, gid: u32) -> Option<(usize, u32)> {
+    if gid == 0 { return None }
+    let i = self.tilesets.iter().rposition(|t| t.firstgid <= gid)?;
+    Some((i, gid - self.tilesets[i].firstgid))
+  }
+  /// properties of gid (flags are ignored)
+  pub fn tile_props(&self, gid: u32) -> Option<&Props> {
+    let (i, id) = self.tileset(gid & !FLIP_MASK)?;
+    self.tilesets[i].props.get(&id)
+  }
+  /// tile layer by name
+  pub fn tile_layer(&self, n: &str) -> Option<&TileLayer> {
+    self.layers.iter().find_map(|l| match l {
+    Layer::Tile(t) if t.name == n => Some(t),
+    _ => None
+    })
+  }
+  /// object layer by name
+  pub fn object_layer(&self, n: &str) -> Option<&ObjectLayer> {
+    self.layers.iter().find_map(|l| match l {
+    Layer::Object(o) if o.name == n => Some(o),
+    _ => None
+    })
+  }
+  /// property of the tile at x y on the layer (for collision etc)
+  pub fn property(&self, l: &TileLayer, x: i32, y: i32, k: &str)
+    -> Option<&Json> {
+    self.tile_props(l.get(x, y).gid).and_then(|p| p.get(k))
+  }
+  /// tile x y at pixel x y
+  pub fn to_tile(&self, px: i32, py: i32) -> (i32, i32) {
+    (px.div_euclid(self.tile_w), py.div_euclid(self.tile_h))
+  }
+  /// tiles x0 y0 x1 y1 (x1 y1 not included) overlapping camera x y w h
+  /// - includes tiles larger than the map tile (drawn to the right top)
+  pub fn visible_range(&self, cam: [i32; 4]) -> [i32; 4] {
+    let [x, y, w, h] = cam;
+    let (tw, th) = (self.tile_w, self.tile_h);
+    let ex = self.tilesets.iter().map(|t| (t.tile_w - 1) / tw)
+      .max().unwrap_or(0).max(0);
+    let ey = self.tilesets.iter().map(|t| (t.tile_h - 1) / th)
+      .max().unwrap_or(0).max(0);
+    [(x.div_euclid(tw) - ex).clamp(0, self.w),
+     y.div_euclid(th).clamp(0, self.h),
+     ((x + w + tw - 1).div_euclid(tw)).clamp(0, self.w),
+     ((y + h + th - 1).div_euclid(th) + ey).clamp(0, self.h)]
+  }
+}
+
+/// extension
+fn ext(n: &str) -> Option<&str> {
+  Path::new(n).extension().and_then(|e| e.to_str())
+}
+
+/// supported map
+fn check_map(orientation: Option<&str>, infinite: bool)
+  -> Result<(), Box<dyn Error>> {
+  if orientation.unwrap_or("orthogonal") != "orthogonal" {
+    return Err("tilemap: only orthogonal maps are supported".into())
+  }
+  if infinite { return Err("tilemap: infinite maps are not supported".into()) }
+  Ok(())
+}
+
+/// check size of the map and its tiles
+fn check_size(m: &TileMapDesc) -> Result<(), Box<dyn Error>> {
+  if m.w < 0 || m.h < 0 {
+    return Err(format!("tilemap: map size {}x{}", m.w, m.h).into())
+  }
+  if m.tile_w <= 0 || m.tile_h <= 0 {
+    return Err(format!("tilemap: tile size {}x{}", m.tile_w, m.tile_h).into())
+  }
+  Ok(())
+}
+
+/// check tile size count and gid range of the tileset
+/// - img: image w h when known
+fn check_tileset(ts: &Tileset, img: Option<(i32, i32)>)
+  -> Result<(), Box<dyn Error>> {
+  if ts.tile_w <= 0 || ts.tile_h <= 0 {
+    return Err(format!("tilemap: tileset {} tile size", ts.name).into())
+  }
+  if ts.count < 0 || ts.columns < 0 || ts.margin < 0 || ts.spacing < 0 {
+    return Err(format!("tilemap: tileset {} negative value", ts.name).into())
+  }
+  if ts.firstgid.checked_add(ts.count as u32).is_none() {
+    return Err(format!("tilemap: tileset {} gid range", ts.name).into())
+  }
+  if let Some((w, h)) = img.filter(|_| ts.image.is_some()) {
+    let c = ts.capacity(w, h);
+    if ts.count > c {
+      return Err(format!("tilemap: tileset {} tilecount {} > {}",
+        ts.name, ts.count, c).into())
+    }
+  }
+  Ok(())
+}
+
+/// number of tiles of the layer w x h
+fn layer_len(w: i32, h: i32) -> Result<usize, Box<dyn Error>> {
+  if w < 0 || h < 0 {
+    return Err(format!("tilemap: layer size {}x{}", w, h).into())
+  }
+  (w as usize).checked_mul(h as usize)
+    .ok_or_else(|| format!("tilemap: layer size {}x{}", w, h).into())
+}
+
+/// decode base64 (standard alphabet, white spaces are ignored)
+fn base64(s: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+  let mut o = vec![];
+  let (mut acc, mut bits) = (0u32, 0);
+  for c in s.bytes().filter(|c| !c.is_ascii_whitespace() && *c != b'=') {
+    let v = match c {
+    b'A'..=b'Z' => c - b'A',
+    b'a'..=b'z' => c - b'a' + 26,
+    b'0'..=b'9' => c - b'0' + 52,
+    b'+' => 62,
+    b'/' => 63,
+    _ => return Err("tilemap: bad base64".into())
+    };
+    acc = (acc << 6) | v as u32;
+    bits += 6;
+    if bits >= 8 {
+      bits -= 8;
+      o.push((acc >> bits) as u8);
+      acc &= (1 << bits) - 1;
+    }
+  }
+  Ok(o)
+}
+
+/// decode csv or base64 layer data
+fn decode_data(encoding: Option<&str>, compression: Option<&str>, s: &str,
+  n: usize) -> Result<Vec<u32>, Box<dyn Error>> {
+  if compression.is_some_and(|c| !c.is_empty()) {
+    return Err("tilemap: compressed layer data is not supported".into())
+  }
+  let d = match encoding {
+  Some("csv") => s.split(',').map(|e| e.trim()).filter(|e| !e.is_empty())
+    .map(|e| e.parse::<u32>()).collect::<Result<Vec<_>, _>>()
+    .map_err(|_| "tilemap: bad csv")?,
+  Some("base64") => base64(s)?.chunks_exact(4)
+    .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect(),
+  e => return Err(format!("tilemap: unknown encoding {:?}", e).into())
+  };
+  if d.len() != n { return Err("tilemap: layer data size".into()) }
+  Ok(d)
+}
+
+/// property value by Tiled type
+fn prop_value(t: Option<&str>, v: &str) -> Json {
+  match t {
+  Some("bool") => Json::Bool(v == "true"),
+  Some("int") | Some("float") | Some("object") =>
+    v.parse().map(Json::Num).unwrap_or(Json::Null),
+  _ => Json::Str(v.to_string())
+  }
+}
+
+/// <properties> of the element
+fn props_from_xml(x: &Xml) -> Props {
+  x.child("properties").map(|p| p.children_named("property").map(|p| {
+    let v = p.attr("value").unwrap_or(&p.text);
+    (p.attr("name").unwrap_or("").to_string(), prop_value(p.attr("type"), v))
+  }).collect()).unwrap_or_default()
+}
+
+/// "properties" of the object
+fn props_from_json(j: &Json) -> Props {
+  j.get("properties").and_then(|p| p.as_arr()).map(|a| a.iter().map(|p|
+    (p.get("name").and_then(|n| n.as_str()).unwrap_or("").to_string(),
+     p.get("value").cloned().unwrap_or(Json::Null))).collect())
+    .unwrap_or_default()
+}
+
+/// tileset (embedded <tileset> or .tsx root)
+fn tileset_from_xml(t: &Xml, firstgid: u32) -> Result<Tileset, Box<dyn Error>> {
+  let int = |k: &str| t.attr_as::<i32>(k).unwrap_or(0);
+  let img = t.child("image");
+  let mut ts = Tileset{firstgid, name: t.attr("name").unwrap_or("").to_string(),
+    tile_w: int("tilewidth"), tile_h: int("tileheight"),
+    columns: int("columns"), count: int("tilecount"),
+    margin: int("margin"), spacing: int("spacing"),
+    image: img.and_then(|i| i.attr("source")).map(|s| s.to_string()),
+    ..Tileset::default()};
+  check_tileset(&ts, img.and_then(|i|
+    i.attr_as("width").zip(i.attr_as("height"))))?;
+  for e in t.children_named("tile") {
+    let id = e.attr_as("id").ok_or("tilemap: tile without id")?;
+    let p = props_from_xml(e);
+    if !p.is_empty() { ts.props.insert(id, p); }
+  }
+  Ok(ts)
+}
+
+/// tileset (embedded or .tsj root)
+fn tileset_from_json(t: &Json, firstgid: u32)
+  -> Result<Tileset, Box<dyn Error>> {
+  let int = |k: &str| t.get(k).and_then(|v| v.as_i32()).unwrap_or(0);
+  let str = |k: &str| t.get(k).and_then(|v| v.as_str());
+  let mut ts = Tileset{firstgid, name: str("name").unwrap_or("").to_string(),
+    tile_w: int("tilewidth"), tile_h: int("tileheight"),
+    columns: int("columns"), count: int("tilecount"),
+    margin: int("margin"), spacing: int("spacing"),
+    image: str("image").map(|s| s.to_string()), ..Tileset::default()};
+  let size = |k: &str| t.get(k).and_then(|v| v.as_i32());
+  check_tileset(&ts, size("imagewidth").zip(size("imageheight")))?;
+  for e in t.get("tiles").and_then(|t| t.as_arr()).unwrap_or(&vec![]) {
+    let id = e.get("id").and_then(|i| i.as_f64())
+      .ok_or("tilemap: tile without id")? as u32;
+    let p = props_from_json(e);
+    if !p.is_empty() { ts.props.insert(id, p); }
+  }
+  Ok(ts)
+}
+
+/// layers of <map> or <group>
+fn layers_from_xml(x: &Xml, v: &mut Vec<Layer>) -> Result<(), Box<dyn Error>> {
+  for l in x.children.iter() {
+    let name = l.attr("name").unwrap_or("").to_string();
+    let visible = l.attr("visible") != Some("0");
+    match l.name.as_str() {
+    "layer" => {
+      let (w, h) = (l.attr_as("width").unwrap_or(0),
+        l.attr_as("height").unwrap_or(0));
+      let n = layer_len(w, h)?;
+      let d = l.child("data").ok_or("tilemap: layer without data")?;
+      let data = match d.attr("encoding") {
+      None => d.children_named("tile").map(|t|
+        t.attr_as("gid").unwrap_or(0)).collect(),
+      e => decode_data(e, d.attr("compression"), &d.text, n)?
+      };
+      if data.len() != n {
+        return Err("tilemap: layer data size".into())
+      }
+      v.push(Layer::Tile(TileLayer{name, w, h, data, visible,
+        opacity: l.attr_as("opacity").unwrap_or(1.0),
+        offset: [l.attr_as("offsetx").unwrap_or(0.0),
+          l.attr_as("offsety").unwrap_or(0.0)], props: props_from_xml(l)}));
+    },
+    "objectgroup" => {
+      let objects = l.children_named("object").map(|o| MapObject{
+        id: o.attr_as("id").unwrap_or(0),
+        name: o.attr("name").unwrap_or("").to_string(),
+        kind: o.attr("type").or(o.attr("class")).unwrap_or("").to_string(),
+        x: o.attr_as("x").unwrap_or(0.0), y: o.attr_as("y").unwrap_or(0.0),
+        w: o.attr_as("width").unwrap_or(0.0),
+        h: o.attr_as("height").unwrap_or(0.0),
+        rotation: o.attr_as("rotation").unwrap_or(0.0),
+        tile: o.attr_as("gid").map(Tile::from_raw),
+        visible: o.attr("visible") != Some("0"), props: props_from_xml(o)
+      }).collect();
+      v.push(Layer::Object(ObjectLayer{name, objects, visible,
+        props: props_from_xml(l)}));
+    },
+    "group" => layers_from_xml(l, v)?,
+    _ => () // tileset properties imagelayer etc
+    }
+  }
+  Ok(())
+}
+
+/// "layers" of map or group
+fn layers_from_json(j: &Json, v: &mut Vec<Layer>)
+  -> Result<(), Box<dyn Error>> {
+  for l in j.get("layers").and_then(|l| l.as_arr()).unwrap_or(&vec![]) {
+    let str = |k: &str| l.get(k).and_then(|v| v.as_str());
+    let f32 = |k: &str, d: f32| l.get(k).and_then(|v| v.as_f64())
+      .map(|v| v as f32).unwrap_or(d);
+    let name = str("name").unwrap_or("").to_string();
+    let visible = l.get("visible").and_then(|v| v.as_bool()).unwrap_or(true);
+    match str("type") {
+    Some("tilelayer") => {
+      let int = |k: &str| l.get(k).and_then(|v| v.as_i32()).unwrap_or(0);
+      let (w, h) = (int("width"), int("height"));
+      let n = layer_len(w, h)?;
+      let data = match l.get("data") {
+      Some(Json::Arr(a)) => a.iter().map(|g|
+        g.as_f64().map(|g| g as u32)).collect::<Option<Vec<_>>>()
+        .ok_or("tilemap: bad layer data")?,
+      Some(Json::Str(s)) => decode_data(str("encoding"), str("compression"),
+        s, n)?,
+      _ => return Err("tilemap: layer without data".into())
+      };
+      if data.len() != n {
+        return Err("tilemap: layer data size".into())
+      }
+      v.push(Layer::Tile(TileLayer{name, w, h, data, visible,
+        opacity: f32("opacity", 1.0),
+        offset: [f32("offsetx", 0.0), f32("offsety", 0.0)],
+        props: props_from_json(l)}));
+    },
+    Some("objectgroup") => {
+      let objects = l.get("objects").and_then(|o| o.as_arr())
+        .unwrap_or(&vec![]).iter().map(|o| {
+        let f = |k: &str| o.get(k).and_then(|v| v.as_f64()).unwrap_or(0.0);
+        let s = |k: &str| o.get(k).and_then(|v| v.as_str());
+        MapObject{id: f("id") as u32, name: s("name").unwrap_or("").to_string(),
+          kind: s("type").or(s("class")).unwrap_or("").to_string(),
+          x: f("x") as f32, y: f("y") as f32,
+          w: f("width") as f32, h: f("height") as f32,
+          rotation: f("rotation") as f32,
+          tile: o.get("gid").and_then(|g| g.as_f64())
+            .map(|g| Tile::from_raw(g as u32)),
+          visible: o.get("visible").and_then(|v| v.as_bool()).unwrap_or(true),
+          props: props_from_json(o)}
+      }).collect();
+      v.push(Layer::Object(ObjectLayer{name, objects, visible,
+        props: props_from_json(l)}));
+    },
+    Some("group") => layers_from_json(l, v)?,
+    _ => () // imagelayer etc
+    }
+  }
+  Ok(())
+}
+
+/// TileMap (renderer)
+pub struct TileMap {
+  /// parsed map
+  pub desc: TileMapDesc,
+  /// texture of each tileset (handle -1 when the tileset has no image)
+  pub texs: Vec<Graph>,
+  /// sub handles of each tileset (local id order)
+  pub tiles: Vec<Vec<Graph>>
+}
+
+/// TileMap
+impl TileMap {
+  /// graph of gid (flags are ignored)
+  pub fn graph(&self, gid: u32) -> Option<&Graph> {
+    let (i, id) = self.desc.tileset(gid & !FLIP_MASK)?;
+    self.tiles[i].get(id as usize)
+  }
+  /// draw visible tile layers
+  /// - cam: x y w h on the map (pixels), (sx, sy): left top on the screen
+  pub fn draw(&self, cam: [i32; 4], sx: i32, sy: i32, trans: i32) {
+    for l in self.desc.layers.iter() {
+      if let Layer::Tile(t) = l {
+        if t.visible { self.draw_layer(t, cam, sx, sy, trans); }
+      }
+    }
+  }
+  /// draw tiles of the layer overlapping the camera
+  pub fn draw_layer(&self, l: &TileLayer, cam: [i32; 4], sx: i32, sy: i32,
+    trans: i32) {
+    let d = &self.desc;
+    let [x0, y0, x1, y1] = d.visible_range(cam);
+    let ox = sx - cam[0] + l.offset[0] as i32;
+    let oy = sy - cam[1] + l.offset[1] as i32;
+    for ty in y0..y1 {
+      for tx in x0..x1 {
+        let t = l.get(tx, ty);
+        let Some((i, id)) = d.tileset(t.gid) else { continue };
+        let Some(g) = self.tiles[i].get(id as usize) else { continue };
+        let ts = &d.tilesets[i];
+        let x = ox + tx * d.tile_w;
+        let y = oy + (ty + 1) * d.tile_h - ts.tile_h; // left bottom aligned
+        if !t.flipped() { g.draw(x, y, trans); continue; }
+        let (a, rx, ry) = t.rota();
+        g.draw_rota_f(x as f32 + ts.tile_w as f32 / 2.0,
+          y as f32 + ts.tile_h as f32 / 2.0, 1.0, a, trans,
+          if rx { TRUE } else { FALSE }, if ry { TRUE } else { FALSE });
+      }
+    }
+  }
+}
@@ -0,0 +1,151 @@
+//! transform ext dx bridge for DxLib
+//!
+
+use crate::dx::*;
+use crate::ext::graph::Graph;
+
+/// SubRect (x y w h on the graph, w or h 0: to the right bottom)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SubRect {
+  /// x
+  pub x: i32,
+  /// y
+  pub y: i32,
+  /// w
+  pub w: i32,
+  /// h
+  pub h: i32
+}
+
+/// SubRect
+impl SubRect {
+  /// whole graph
+  pub const WHOLE: SubRect = SubRect{x: 0, y: 0, w: 0, h: 0};
+  /// constructor
+  pub fn new(x: i32, y: i32, w: i32, h: i32) -> Self { SubRect{x, y, w, h} }
+  /// x y w h on the graph of the size
+  pub fn resolve(&self, sz: (i32, i32)) -> [i32; 4] {
+    let w = if self.w > 0 { self.w } else { sz.0 - self.x };
+    let h = if self.h > 0 { self.h } else { sz.1 - self.y };
+    [self.x, self.y, w, h]
+  }
+}
+
+/// Transform2D
+/// - maps a point of the image (relative to origin) to the parent space:
+///   pos + rotate(rotation, scale * flip * p)
+/// - flip mirrors the image (as DxLib reverse flags) and the children
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform2D {
+  /// position of the origin in the parent space
+  pub pos: [f32; 2],
+  /// pivot on the image (pixels from the left top of the sub rect)
+  pub origin: [f32; 2],
+  /// scale x y
+  pub scale: [f32; 2],
+  /// rotation radians clockwise
+  pub rotation: f64,
+  /// mirror LR
+  pub flip_x: bool,
+  /// mirror TB
+  pub flip_y: bool
+}
+
+/// Default for Transform2D
+impl Default for Transform2D {
+  /// identity
+  fn default() -> Self {
+    Transform2D{pos: [0.0, 0.0], origin: [0.0, 0.0], scale: [1.0, 1.0],
+      rotation: 0.0, flip_x: false, flip_y: false}
+  }
+}
+
+/// Transform2D
+impl Transform2D {
+  /// translation
+  pub fn new(x: f32, y: f32) -> Self {
+    Transform2D{pos: [x, y], ..Transform2D::default()}
+  }
+  /// with origin
+  pub fn origin(mut self, ox: f32, oy: f32) -> Self {
+    self.origin = [ox, oy];
+    self
+  }
+  /// with scale
+  pub fn scale(mut self, sx: f32, sy: f32) -> Self {
+    self.scale = [sx, sy];
+    self
+  }
+  /// with rotation
+  pub fn rotate(mut self, a: f64) -> Self {
+    self.rotation = a;
+    self
+  }
+  /// with flip
+  pub fn flip(mut self, fx: bool, fy: bool) -> Self {
+    (self.flip_x, self.flip_y) = (fx, fy);
+    self
+  }
+  /// point relative to origin to the parent space
+  pub fn apply(&self, p: [f32; 2]) -> [f32; 2] {
+    let x = p[0] * self.scale[0] * if self.flip_x { -1.0 } else { 1.0 };
+    let y = p[1] * self.scale[1] * if self.flip_y { -1.0 } else { 1.0 };
+    let (s, c) = (self.rotation.sin() as f32, self.rotation.cos() as f32);
+    [self.pos[0] + x * c - y * s, self.pos[1] + x * s + y * c]
+  }
+  /// child (pos in the space of self) to the parent space of self
+  /// - exact when the scale of self is uniform (no shear)
+  pub fn then(&self, child: &Transform2D) -> Transform2D {
+    let mirror = self.flip_x != self.flip_y; // one reflection
+    Transform2D{pos: self.apply(child.pos), origin: child.origin,
+      scale: [self.scale[0] * child.scale[0], self.scale[1] * child.scale[1]],
+      rotation: if mirror { self.rotation - child.rotation }
+        else { self.rotation + child.rotation },
+      flip_x: self.flip_x != child.flip_x, flip_y: self.flip_y != child.flip_y}
+  }
+}
+
+/// float and transform draw for Graph
+impl Graph {
+  /// draw (float left top)
+  pub fn draw_f(&self, x: f32, y: f32, trans: i32) {
+    unsafe { DrawGraphF(x, y, self.h, trans); }
+  }
+  /// draw rect rotate (center of the rect at x y)
+  pub fn draw_rect_rota(&self, x: i32, y: i32, srcx: i32, srcy: i32,
+    w: i32, h: i32, extrate: f64, angle: f64,
+    trans: i32, reversex: i32, reversey: i32) {
+    unsafe { DrawRectRotaGraph(x, y, srcx, srcy, w, h, extrate, angle,
+      self.h, trans, reversex, reversey); }
+  }
+  /// draw rect rotate (float center)
+  pub fn draw_rect_rota_f(&self, x: f32, y: f32, srcx: i32, srcy: i32,
+    w: i32, h: i32, extrate: f64, angle: f64,
+    trans: i32, reversex: i32, reversey: i32) {
+    unsafe { DrawRectRotaGraphF(x, y, srcx, srcy, w, h, extrate, angle,
+      self.h, trans, reversex, reversey); }
+  }
+  /// draw rotate around cx cy of the graph (placed at x y) scale x y
+  pub fn draw_rota3(&self, x: i32, y: i32, cx: i32, cy: i32,
+    extratex: f64, extratey: f64, angle: f64,
+    trans: i32, reversex: i32, reversey: i32) {
+    unsafe { DrawRotaGraph3(x, y, cx, cy, extratex, extratey, angle,
+      self.h, trans, reversex, reversey); }
+  }
+  /// draw rotate around cx cy of the graph (float)
+  pub fn draw_rota3_f(&self, x: f32, y: f32, cx: f32, cy: f32,
+    extratex: f64, extratey: f64, angle: f64,
+    trans: i32, reversex: i32, reversey: i32) {
+    unsafe { DrawRotaGraph3F(x, y, cx, cy, extratex, extratey, angle,
+      self.h, trans, reversex, reversey); }
+  }
+  /// draw sub rect by transform (position origin scale rotation flip)
+  pub fn draw_with(&self, t: &Transform2D, r: &SubRect, trans: i32) {
+    let [x, y, w, h] = if r.w > 0 && r.h > 0 { [r.x, r.y, r.w, r.h] }
+      else { r.resolve(self.get_size()) };
+    let b = |f: bool| if f { TRUE } else { FALSE };
+    unsafe { DrawRectRotaGraph3F(t.pos[0], t.pos[1], x, y, w, h,
+      t.origin[0], t.origin[1], t.scale[0] as f64, t.scale[1] as f64,
+      t.rotation, self.h, trans, b(t.flip_x), b(t.flip_y)); }
+  }
+}
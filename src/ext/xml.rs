@@ -0,0 +1,182 @@
+//! xml ext dx bridge for DxLib
+//!
+//! minimum XML reader for resource descriptors (Tiled TMX TSX etc)
+//! - no DTD, no namespaces, CDATA is read as text
+//!
+
+use std::error::Error;
+
+/// Xml (element)
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Xml {
+  /// tag name
+  pub name: String,
+  /// attributes (keep order)
+  pub attrs: Vec<(String, String)>,
+  /// child elements
+  pub children: Vec<Xml>,
+  /// text (concatenated, not trimmed)
+  pub text: String
+}
+
+/// Xml
+impl Xml {
+  /// parse (root element)
+  pub fn parse(s: &str) -> Result<Xml, Box<dyn Error>> {
+    let mut p = Parser{s, i: 0};
+    p.misc()?;
+    let e = p.element()?;
+    p.misc()?;
+    if p.i != s.len() { return Err(p.err("trailing characters")) }
+    Ok(e)
+  }
+  /// attribute
+  pub fn attr(&self, k: &str) -> Option<&str> {
+    self.attrs.iter().find(|(n, _)| n == k).map(|(_, v)| v.as_str())
+  }
+  /// attribute parsed as T
+  pub fn attr_as<T: std::str::FromStr>(&self, k: &str) -> Option<T> {
+    self.attr(k).and_then(|v| v.trim().parse().ok())
+  }
+  /// first child by name
+  pub fn child(&self, n: &str) -> Option<&Xml> {
+    self.children.iter().find(|c| c.name == n)
+  }
+  /// children by name
+  pub fn children_named<'a>(&'a self, n: &'a str)
+    -> impl Iterator<Item=&'a Xml> + 'a {
+    self.children.iter().filter(move |c| c.name == n)
+  }
+}
+
+/// replace entities
+fn unescape(s: &str) -> Result<String, Box<dyn Error>> {
+  let mut o = String::with_capacity(s.len());
+  let mut r = s;
+  while let Some(p) = r.find('&') {
+    o.push_str(&r[..p]);
+    let e = r[p..].find(';').ok_or("xml: unterminated entity")?;
+    let n = &r[p + 1..p + e];
+    match n {
+    "amp" => o.push('&'),
+    "lt" => o.push('<'),
+    "gt" => o.push('>'),
+    "quot" => o.push('"'),
+    "apos" => o.push('\''),
+    _ => {
+      let u = if let Some(h) = n.strip_prefix("#x") {
+        u32::from_str_radix(h, 16)?
+      } else if let Some(d) = n.strip_prefix('#') {
+        d.parse()?
+      } else { return Err(format!("xml: unknown entity {}", n).into()) };
+      o.push(char::from_u32(u).unwrap_or('\u{fffd}'));
+    }
+    }
+    r = &r[p + e + 1..];
+  }
+  o.push_str(r);
+  Ok(o)
+}
+
+/// Parser
+struct Parser<'a> {
+  /// source
+  s: &'a str,
+  /// position
+  i: usize
+}
+
+/// Parser
+impl Parser<'_> {
+  /// error with position
+  fn err(&self, m: &str) -> Box<dyn Error> {
+    format!("xml: {} at {}", m, self.i).into()
+  }
+  /// rest
+  fn rest(&self) -> &str { &self.s[self.i..] }
+  /// skip white spaces
+  fn ws(&mut self) {
+    let r = self.rest();
+    self.i += r.len() - r.trim_start().len();
+  }
+  /// skip to after t
+  fn skip_past(&mut self, t: &str) -> Result<(), Box<dyn Error>> {
+    let p = self.rest().find(t).ok_or_else(|| self.err("unterminated"))?;
+    self.i += p + t.len();
+    Ok(())
+  }
+  /// skip declaration comments doctype
+  fn misc(&mut self) -> Result<(), Box<dyn Error>> {
+    loop {
+      self.ws();
+      let r = self.rest();
+      if r.starts_with("<?") { self.skip_past("?>")?; }
+      else if r.starts_with("<!--") { self.skip_past("-->")?; }
+      else if r.starts_with("<!") { self.skip_past(">")?; }
+      else { return Ok(()) }
+    }
+  }
+  /// name
+  fn name(&mut self) -> Result<String, Box<dyn Error>> {
+    let r = self.rest();
+    let n = r.find(|c: char| c.is_whitespace() || "/>=".contains(c))
+      .unwrap_or(r.len());
+    if n == 0 { return Err(self.err("expected name")) }
+    let t = r[..n].to_string();
+    self.i += n;
+    Ok(t)
+  }
+  /// element
+  fn element(&mut self) -> Result<Xml, Box<dyn Error>> {
+    if !self.rest().starts_with('<') { return Err(self.err("expected '<'")) }
+    self.i += 1;
+    let mut e = Xml{name: self.name()?, ..Xml::default()};
+    loop {
+      self.ws();
+      let r = self.rest();
+      if r.starts_with("/>") { self.i += 2; return Ok(e) }
+      if r.starts_with('>') { self.i += 1; break }
+      let k = self.name()?;
+      self.ws();
+      if !self.rest().starts_with('=') { return Err(self.err("expected '='")) }
+      self.i += 1;
+      self.ws();
+      let q = self.rest().chars().next()
+        .filter(|q| *q == '"' || *q == '\'')
+        .ok_or_else(|| self.err("expected quote"))?;
+      self.i += 1;
+      let p = self.rest().find(q).ok_or_else(|| self.err("unterminated"))?;
+      let v = unescape(&self.rest()[..p])?;
+      self.i += p + 1;
+      e.attrs.push((k, v));
+    }
+    loop {
+      let r = self.rest();
+      let p = r.find('<').ok_or_else(|| self.err("unterminated element"))?;
+      e.text.push_str(&unescape(&r[..p])?);
+      self.i += p;
+      let r = self.rest();
+      if r.starts_with("</") {
+        self.i += 2;
+        let n = self.name()?;
+        if n != e.name { return Err(self.err("mismatched end tag")) }
+        self.ws();
+        if !self.rest().starts_with('>') {
+          return Err(self.err("expected '>'"))
+        }
+        self.i += 1;
+        return Ok(e)
+      } else if r.starts_with("<![CDATA[") {
+        self.i += 9;
+        let p = self.rest().find("]]>").ok_or_else(|| self.err("CDATA"))?;
+        e.text.push_str(&self.rest()[..p]);
+        self.i += p + 3;
+      } else if r.starts_with("<!--") || r.starts_with("<?") {
+        let t = if r.starts_with("<?") { "?>" } else { "-->" };
+        self.skip_past(t)?;
+      } else {
+        e.children.push(self.element()?);
+      }
+    }
+  }
+}
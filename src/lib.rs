@@ -10,7 +10,35 @@ pub mod demo;
 #[cfg(test)]
 mod tests {
   // use super::*;
-  use crate::dx::{COLOR_F, COLOR_U8, FLOAT4};
+  use crate::dx::{COLOR_F, COLOR_U8, FLOAT4, FLOAT2, VECTOR, MATRIX};
+  use crate::dx::{TRUE, DX_BLENDMODE_NOBLEND};
+  use crate::dx::{DX_SHADERTYPE_PIXEL, DX_SHADERTYPE_VERTEX};
+  use crate::ext::render::{RenderState, RenderChange, RenderStack};
+  use crate::ext::atlas::{AtlasDesc, AtlasFrame};
+  use crate::ext::slice::{Insets, SliceMode, nine_slice_pieces};
+  use crate::ext::image::{ImageInfo, ImageKind, DivGrid};
+  use crate::ext::transform::{Transform2D, SubRect};
+  use crate::ext::json::Json;
+  use crate::ext::cbuffer::{HlslType, hlsl_field_ok};
+  use crate::ext::hlsl::Hlsl;
+  use crate::ext::graph::{Graph, ImageFormat};
+  use crate::ext::soft::rgba_len;
+  use crate::ext::shader::{ConstantBuffer, PixelShader, VertexShader};
+  use crate::ext::program::ProgramDesc;
+  use crate::ext::tdx::Tr;
+  use crate::ext::reload::{HotReload, ReloadKind};
+  use crate::ext::light::LightParamSub;
+  use crate::dx::DX_LIGHTTYPE_SPOT;
+  use crate::ext::shader::{current_shader, swap_shader, release_shader};
+  use std::time::{Duration, SystemTime};
+  use crate::ext::tilemap::{TileMapDesc, Tile, FLIP_D, FLIP_H, FLIP_V};
+  use crate::ext::anim::{Animation, Clip, Player, PlayMode};
+  use crate::ext::batch::Batch2D;
+  use crate::ext::filter::{Filter, Blend};
+  use crate::ext::post::ping_pong;
+  use crate::dx::{DX_GRAPH_FILTER_MONO, DX_GRAPH_FILTER_BRIGHT_CLIP};
+  use crate::dx::{DX_GRAPH_FILTER_REPLACEMENT, DX_CMP_LESS};
+  use crate::dx::{DX_GRAPH_BLEND_ADD, DX_GRAPH_BLEND_RGBA_SELECT_MIX};
   use crate::demo;
 
   /// test COLOR_F
@@ -50,6 +78,556 @@ mod tests {
     assert_eq!(COLOR_U8::new(64, 128, 255, 0).as_u32(), 0x00ff8040u32);
   }
 
+  /// test RenderState diff
+  #[test]
+  fn test_render_state() {
+    let a = RenderState::new();
+    assert_eq!(a.diff(None).len(), 7);
+    assert_eq!(a.diff(Some(&a.clone())), vec![]);
+    let b = RenderState{use_z_buffer: TRUE, blend_param: 128, ..a.clone()};
+    assert_eq!(b.diff(Some(&a)), vec![
+      RenderChange::BlendMode(DX_BLENDMODE_NOBLEND, 128),
+      RenderChange::UseZBuffer(TRUE)]);
+    let mut st = RenderStack::new();
+    assert!(st.push(&b).is_err()); // state is unknown
+    assert_eq!(st.apply(&a), 7);
+    assert_eq!(st.push(&b).ok(), Some(2));
+    assert_eq!((st.pop(), st.cur.as_ref()), (2, Some(&a)));
+  }
+
+  /// test SpriteAtlas descriptors
+  #[test]
+  fn test_atlas_desc() {
+    let h = AtlasDesc::from_json(r#"{"frames": {
+      "a.png": {"frame": {"x": 2, "y": 4, "w": 30, "h": 20},
+        "rotated": true, "trimmed": true,
+        "spriteSourceSize": {"x": 1, "y": 3, "w": 30, "h": 20},
+        "sourceSize": {"w": 32, "h": 24}},
+      "b.png": {"frame": {"x": 40, "y": 0, "w": 8, "h": 8}}},
+      "meta": {"image": "sheet.png"}}"#).expect("hash");
+    assert_eq!(h.image, Some("sheet.png".to_string()));
+    assert_eq!(h.frames[0].sheet_rect(), [2, 4, 20, 30]);
+    assert_eq!(h.frames[0].center_offset(), (0.0, 1.0));
+    assert!(h.frames[0].trimmed() && !h.frames[1].trimmed());
+    let a = AtlasDesc::from_json(r#"{"frames": [{"filename": "b.png",
+      "frame": {"x": 40, "y": 0, "w": 8, "h": 8}}]}"#).expect("array");
+    assert_eq!(a.frames, vec![AtlasFrame::new("b.png", 40, 0, 8, 8)]);
+    let g = AtlasDesc::from_toml_grid("image = \"g.png\"\n[grid]\n\
+      cell_w = 64\ncell_h = 32 # half\ncolumns = 4\nrows = 3\ncount = 6\n\
+      spacing = 2\nnames = [\"idle\", \"walk\"]\n").expect("toml");
+    assert_eq!(g.frames.len(), 6);
+    assert_eq!(g.frames[1], AtlasFrame::new("walk", 66, 0, 64, 32));
+    assert_eq!(g.frames[5], AtlasFrame::new("frame_5", 66, 34, 64, 32));
+    assert!(AtlasDesc::from_toml_grid("[grid]\ncell_w = 1\n").is_err());
+    let d = AtlasDesc::from_toml_grid(r#"
+image = "_img_256x256_16x64x64.png"
+[grid]
+cell_w = 64
+cell_h = 64
+columns = 4
+rows = 3
+count = 12 # default columns * rows
+margin = 0 # default 0
+spacing = 0 # default 0
+prefix = "walk_" # default "frame_" (names walk_0 walk_1 ...)
+# names = ["idle", "walk"] # overrides prefix
+# insets = [8, 8, 8, 8] # all frames
+[insets] # each frame (overrides grid.insets)
+walk_0 = [4, 4, 4, 4]
+"#).expect("doc");
+    assert_eq!(d.image, Some("_img_256x256_16x64x64.png".to_string()));
+    assert_eq!(d.frames.len(), 12);
+    assert_eq!(d.frames[0].insets, Some(Insets::all(4)));
+    assert_eq!(d.frames[5], AtlasFrame::new("walk_5", 64, 64, 64, 64));
+    let q = AtlasDesc::from_toml_grid("image = \"#a\" # b\n[grid]\n\
+      cell_w = 1\ncell_h = 1\ncolumns = 1\nrows = 1\n\
+      names = [\"x#y\"] # z\n").expect("quoted #");
+    assert_eq!(q.image, Some("#a".to_string()));
+    assert_eq!(q.frames[0].name, "x#y");
+  }
+
+  /// test nine slice
+  #[test]
+  fn test_nine_slice() {
+    let ins = Insets::all(4);
+    let p = nine_slice_pieces((16, 16), [10, 20, 40, 24], &ins,
+      SliceMode::STRETCH);
+    assert_eq!(p.len(), 9);
+    assert_eq!(p[0], ([10, 20, 4, 4], [0, 0, 4, 4]));
+    assert_eq!(p[4], ([14, 24, 32, 16], [4, 4, 8, 8]));
+    assert_eq!(p[8], ([46, 40, 4, 4], [12, 12, 4, 4]));
+    let p = nine_slice_pieces((16, 16), [0, 0, 28, 16], &ins, SliceMode::TILE);
+    let top = p.iter().filter(|(d, _)| d[1] == 0).collect::<Vec<_>>();
+    assert_eq!(top.iter().map(|(d, s)| (d[0], d[2], s[2])).collect::<Vec<_>>(),
+      vec![(0, 4, 4), (4, 8, 8), (12, 8, 8), (20, 4, 4), (24, 4, 4)]);
+    let p = nine_slice_pieces((16, 16), [0, 0, 6, 6], &ins, SliceMode::TILE);
+    assert_eq!(p.iter().map(|(d, _)| d[2] * d[3]).sum::<i32>(), 36);
+    let g = AtlasDesc::from_toml_grid("[grid]\ncell_w = 16\ncell_h = 16\n\
+      columns = 2\nrows = 1\ninsets = [4, 4, 4, 4]\n\
+      [insets]\nframe_1 = [2, 3, 4, 5]\n").expect("insets");
+    assert_eq!(g.frames[0].insets, Some(ins));
+    assert_eq!(g.frames[1].insets, Some(Insets::new(2, 3, 4, 5)));
+    let j = AtlasDesc::from_json(r#"{"frames": {"p": {"insets": [1, 2, 3, 4],
+      "frame": {"x": 0, "y": 0, "w": 8, "h": 8}}}}"#).expect("json insets");
+    assert_eq!(j.frames[0].insets, Some(Insets::new(1, 2, 3, 4)));
+  }
+
+  /// test image header and div grid
+  #[test]
+  fn test_image_header() {
+    let r = |n: &str| ImageInfo::load(&format!("./resource/{}", n));
+    let p = r("_img_320x240_0000.png").expect("png");
+    assert_eq!((p.kind, p.w, p.h), (ImageKind::Png, 320, 240));
+    let b = r("_texture_128x128_0000.bmp").expect("bmp");
+    assert_eq!((b.kind, b.w, b.h), (ImageKind::Bmp, 128, 128));
+    assert!(r("shader_VS.hlsl").is_err());
+    let j = [0xff, 0xd8, 0xff, 0xe0, 0x00, 0x04, 0x4a, 0x46,
+      0xff, 0xc2, 0x00, 0x0b, 0x08, 0x00, 0xf0, 0x01, 0x40, 0x01, 0x01];
+    let j = ImageInfo::from_bytes(&j).expect("jpeg");
+    assert_eq!((j.kind, j.w, j.h), (ImageKind::Jpeg, 320, 240));
+    let a = r("_img_256x256_16x64x64.png").expect("ani");
+    let g = DivGrid{allnum: 12, xnum: 4, ynum: 3, xsz: 64, ysz: 64,
+      xstride: 0, ystride: 0};
+    assert!(g.check(a.w, a.h).is_ok());
+    assert!(DivGrid{allnum: 0, ..g}.check(a.w, a.h).is_err());
+    assert!(DivGrid{allnum: 13, ..g}.check(a.w, a.h).is_err());
+    assert!(DivGrid{xsz: 65, ..g}.check(a.w, a.h).is_err());
+    assert!(DivGrid{xstride: 66, ..g}.check(a.w, a.h).is_err());
+    assert!(DivGrid{allnum: 13, ynum: 4, ..g}.check(a.w, a.h).is_ok());
+    let m = i32::MAX;
+    assert!(DivGrid{xnum: m, ynum: m, ..g}.check(m, m).is_ok()); // no overflow
+    assert!(DivGrid{allnum: m, xnum: m, ynum: 1, ..g}.check(m, m).is_err());
+    assert!(DivGrid{xstride: m, ..g}.check(m, m).is_err());
+    assert!(DivGrid::auto(m, m, 1, 1).is_err());
+    let k = r("_img_64x64_64x8x8.png").expect("blk");
+    let g = DivGrid{allnum: 8, xnum: 1, ynum: 8, xsz: 8, ysz: 8,
+      xstride: 8, ystride: 0};
+    assert!(g.check(k.w, k.h).is_ok());
+    assert_eq!(DivGrid::auto(k.w, k.h, 8, 8).expect("auto").allnum, 64);
+    assert_eq!(DivGrid::auto(a.w, a.h, 100, 64).expect("auto").xnum, 2);
+    assert!(DivGrid::auto(a.w, a.h, 300, 64).is_err());
+    let mut bmp = vec![0u8; 26];
+    bmp[..2].copy_from_slice(b"BM");
+    bmp[14] = 40;
+    bmp[22..26].copy_from_slice(&i32::MIN.to_le_bytes());
+    assert!(ImageInfo::from_bytes(&bmp).is_err()); // no panic
+    bmp[22..26].copy_from_slice(&(-8i32).to_le_bytes());
+    assert_eq!(ImageInfo::from_bytes(&bmp).expect("top down").h, 8);
+    assert!(ImageInfo::from_bytes(&[0x89, b'P', b'N', b'G']).is_err());
+    let q = |n: &str| ImageInfo::probe(&format!("./resource/{}", n));
+    assert!(q("shader_VS.hlsl").expect("unknown").is_none());
+    assert!(q("no_such_file.png").expect("left to DxLib").is_none());
+    assert_eq!(q("_img_320x240_0000.png").expect("png"), Some(p));
+  }
+
+  /// test Transform2D
+  #[test]
+  fn test_transform() {
+    let near = |a: [f32; 2], b: [f32; 2]|
+      (a[0] - b[0]).abs() < 1e-4 && (a[1] - b[1]).abs() < 1e-4;
+    let h = std::f64::consts::FRAC_PI_2;
+    let p = Transform2D::new(100.0, 50.0).scale(2.0, 2.0).rotate(h);
+    assert!(near(p.apply([10.0, 0.0]), [100.0, 70.0]));
+    let c = Transform2D::new(10.0, 0.0).rotate(h).origin(4.0, 4.0);
+    let w = p.then(&c);
+    assert!(near(w.pos, [100.0, 70.0]));
+    assert_eq!((w.scale, w.rotation), ([2.0, 2.0], 2.0 * h));
+    assert!(near(w.apply([1.0, 0.0]), p.apply(c.apply([1.0, 0.0]))));
+    let m = Transform2D::new(0.0, 0.0).flip(true, false);
+    let w = m.then(&c);
+    assert_eq!((w.rotation, w.flip_x, w.flip_y), (-h, true, false));
+    assert!(near(w.apply([3.0, 1.0]), m.apply(c.apply([3.0, 1.0]))));
+    assert!(!m.then(&w).flip_x);
+    assert_eq!(SubRect::WHOLE.resolve((64, 32)), [0, 0, 64, 32]);
+    assert_eq!(SubRect::new(8, 4, 0, 0).resolve((64, 32)), [8, 4, 56, 28]);
+  }
+
+  /// test TileMap parser
+  #[test]
+  fn test_tilemap() {
+    let tmx = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" width="2" height="2"
+ tilewidth="16" tileheight="16" infinite="0">
+ <tileset firstgid="1" name="t" tilewidth="16" tileheight="16"
+  tilecount="8" columns="4" spacing="1" margin="2">
+  <image source="t.png" width="70" height="36"/>
+  <tile id="1"><properties>
+   <property name="solid" type="bool" value="true"/>
+   <property name="note" value="a &amp; b"/>
+  </properties></tile>
+ </tileset>
+ <tileset firstgid="9" source="big.tsx"/>
+ <!-- comment -->
+ <layer id="1" name="ground" width="2" height="2">
+  <data encoding="csv">
+1,2147483650,
+536870915,0
+</data>
+ </layer>
+ <group name="g"><layer name="b64" width="2" height="2" offsetx="4">
+  <data encoding="base64">AQAAAAIAAIADAAAgAAAAAA==</data></layer></group>
+ <objectgroup name="obj">
+  <object id="3" name="spawn" type="player" x="8" y="24.5"
+   width="16" height="16"><properties>
+   <property name="hp" type="int" value="3"/></properties></object>
+  <object id="4" gid="1073741826" x="0" y="32"/>
+ </objectgroup>
+</map>"#;
+    let m = TileMapDesc::from_tmx(tmx).expect("tmx");
+    assert_eq!((m.w, m.h, m.tile_w, m.tile_h), (2, 2, 16, 16));
+    assert_eq!(m.tilesets[0].tile_rect(5), [19, 19, 16, 16]);
+    assert_eq!(m.tilesets[1].source, Some("big.tsx".to_string()));
+    assert_eq!(m.tileset(10), Some((1, 1)));
+    let g = m.tile_layer("ground").expect("ground");
+    assert_eq!(g.data, m.tile_layer("b64").expect("b64").data);
+    assert_eq!(g.get(1, 0), Tile{gid: 2, flip_h: true, ..Tile::default()});
+    assert!(g.get(0, 1).flip_d && g.get(1, 1).is_empty());
+    assert_eq!(g.get(5, 5), Tile::default());
+    assert_eq!(m.property(g, 1, 0, "solid"), Some(&Json::Bool(true)));
+    assert_eq!(m.property(g, 0, 0, "solid"), None);
+    assert_eq!(m.tile_props(2).and_then(|p| p.get("note")),
+      Some(&Json::Str("a & b".to_string())));
+    let o = m.object_layer("obj").expect("obj");
+    assert_eq!((o.objects[0].kind.as_str(), o.objects[0].y), ("player", 24.5));
+    assert_eq!(o.objects[0].props.get("hp"), Some(&Json::Num(3.0)));
+    assert!(o.objects[1].tile.expect("tile").flip_v);
+    assert_eq!(Tile::from_raw(FLIP_D | 5).rota(),
+      (std::f64::consts::FRAC_PI_2, false, true));
+    assert_eq!(Tile::from_raw(FLIP_H | FLIP_V | 5).rota(), (0.0, true, true));
+    let j = TileMapDesc::from_json(r#"{"width": 3, "height": 1,
+      "tilewidth": 16, "tileheight": 16,
+      "tilesets": [{"firstgid": 1, "name": "t", "tilewidth": 16,
+        "tileheight": 32, "tilecount": 4, "columns": 2, "image": "t.png",
+        "tiles": [{"id": 0, "properties": [
+          {"name": "solid", "type": "bool", "value": true}]}]}],
+      "layers": [{"type": "group", "layers": [{"type": "tilelayer",
+        "name": "a", "width": 3, "height": 1, "data": [1, 0, 2]}]},
+        {"type": "objectgroup", "name": "o", "objects": []}]}"#)
+      .expect("json");
+    let a = j.tile_layer("a").expect("a");
+    assert_eq!(j.property(a, 0, 0, "solid"), Some(&Json::Bool(true)));
+    assert!(j.object_layer("o").is_some());
+    let mut v = TileMapDesc{w: 40, h: 30, tile_w: 16, tile_h: 16,
+      ..TileMapDesc::default()};
+    assert_eq!(v.visible_range([20, 0, 32, 16]), [1, 0, 4, 1]);
+    assert_eq!(v.visible_range([-50, -50, 40, 40]), [0, 0, 0, 0]);
+    v.tilesets = j.tilesets.clone(); // 16x32 tiles reach one row above
+    assert_eq!(v.visible_range([0, 0, 16, 16]), [0, 0, 1, 2]);
+    assert_eq!(v.to_tile(-1, 17), (-1, 1));
+    assert!(TileMapDesc::from_tmx("<map orientation='isometric'/>").is_err());
+    assert!(TileMapDesc::from_json(r#"{"width": 1, "height": 1,
+      "tilewidth": 16, "tileheight": 16, "layers": [{"type": "tilelayer",
+        "width": 1, "height": 1, "data": "eJw=", "compression": "zlib"}]}"#)
+      .is_err());
+    assert!(TileMapDesc::from_tmx(
+      "<map width='1' height='1' tilewidth='0' tileheight='16'/>").is_err());
+    assert!(TileMapDesc::from_json(r#"{"width": -1, "height": 1,
+      "tilewidth": 16, "tileheight": 16, "layers": []}"#).is_err());
+    let ts = |c: &str| TileMapDesc::from_json(&format!(r#"{{"width": 1,
+      "height": 1, "tilewidth": 16, "tileheight": 16, "tilesets": [{{
+        "firstgid": 1, "tilewidth": 16, "tileheight": 16, "columns": 4,
+        "image": "t.png", "imagewidth": 64, "imageheight": 32,
+        "tilecount": {}}}]}}"#, c));
+    assert!(ts("8").is_ok());
+    assert!(ts("9").is_err() && ts("-1").is_err());
+    assert!(ts("2147483647").is_err());
+    assert!(TileMapDesc::from_json(r#"{"width": 1, "height": 1,
+      "tilewidth": 16, "tileheight": 16, "tilesets": [{"firstgid": 4294967295,
+        "tilewidth": 16, "tileheight": 16, "tilecount": 2}]}"#).is_err());
+    assert_eq!(m.tilesets[0].capacity(67, 36), 6); // 3 columns fit
+    assert!(!m.tilesets[0].contains(0) && m.tilesets[0].contains(8));
+    assert!(!m.tilesets[0].contains(9) && !m.tilesets[0].contains(u32::MAX));
+    assert!(TileMapDesc::from_json(r#"{"width": 1, "height": 1,
+      "tilewidth": 16, "tileheight": 16, "layers": [{"type": "tilelayer",
+        "width": 65536, "height": 65536, "data": []}]}"#).is_err());
+    assert!(TileMapDesc::from_tmx("<map width='1' height='1' tilewidth='16' \
+      tileheight='16'><layer width='-1' height='-1'><data/></layer></map>")
+      .is_err());
+  }
+
+  /// test Animation Player
+  #[test]
+  fn test_anim_player() {
+    let run = |c: &Clip, n: usize, dt: f32| {
+      let mut p = Player::new();
+      (0..n).map(|_| { p.update(c, dt); p.frame(c) }).collect::<Vec<_>>()
+    };
+    let c = Clip::range(10, 3, 2.0, PlayMode::Loop).expect("clip");
+    assert_eq!(run(&c, 7, 1.0), vec![10, 11, 11, 12, 12, 10, 10]);
+    let c = Clip::range(0, 3, 1.0, PlayMode::Once).expect("clip");
+    assert_eq!(run(&c, 5, 1.0), vec![1, 2, 2, 2, 2]);
+    let c = Clip::range(0, 3, 1.0, PlayMode::PingPong).expect("clip");
+    assert_eq!(run(&c, 6, 1.0), vec![1, 2, 1, 0, 1, 2]);
+    assert_eq!(c.period(), Some(4.0));
+    assert_eq!(run(&c, 2, 4e6 + 1.0), vec![1, 2]); // whole periods skipped
+    assert!(Clip::new(vec![0], 0.0, PlayMode::Loop).is_err());
+    assert!(Clip::new(vec![0], f32::NAN, PlayMode::Loop).is_err());
+    assert!(Clip::new(vec![], 1.0, PlayMode::Loop).is_err());
+    assert!(Clip::with_durations(vec![0, 1], vec![1.0], PlayMode::Loop)
+      .is_err());
+    let c = Clip::with_durations(vec![0, 1], vec![1.0, 3.0], PlayMode::Loop)
+      .expect("clip").event(0, "step").event(1, "land");
+    let mut p = Player::new();
+    assert_eq!(p.update(&c, 0.5), vec!["step"]);
+    assert_eq!(p.update(&c, 4.0), vec!["land", "step"]);
+    p.speed = 0.0;
+    assert_eq!(p.update(&c, 10.0), Vec::<String>::new());
+    p.speed = 2.0;
+    assert_eq!((p.update(&c, 0.25), p.frame(&c)), (vec!["land".to_string()], 1));
+    let mut a = Animation::new(&[]);
+    a.add_clip("walk", Clip::range(0, 4, 1.0, PlayMode::Loop).expect("clip"));
+    assert!(a.play("walk"));
+    (0..3).for_each(|_| { a.update(1.0); });
+    assert_eq!(a.player.pos, 3);
+    a.add_clip("walk", Clip::range(0, 2, 1.0, PlayMode::Loop).expect("clip"));
+    assert_eq!(a.player.pos, 0); // replaced
+    a.update(1.0);
+    assert_eq!(a.player.pos, 1);
+    a.player.pos = 5; // out of the clip
+    a.update(0.5);
+    assert_eq!(a.player.pos, 0);
+  }
+
+  /// test cbuffer packing
+  #[test]
+  fn test_cbuffer() {
+    crate::cbuffer! {
+      struct Packed {
+        a: VECTOR, // float3 + float in a register
+        b: f32,
+        c: FLOAT2, // float2 + float2
+        d: FLOAT2,
+        m: MATRIX,
+        r: [FLOAT4; 2]
+      }
+    }
+    assert_eq!(std::mem::size_of::<Packed>(), 128);
+    assert_eq!(<Packed as HlslType>::SIZE, 128);
+    assert_eq!(<[FLOAT4; 3] as HlslType>::ELEM, 16);
+    assert!(hlsl_field_ok(12, 4, 0));
+    assert!(hlsl_field_ok(8, 8, 0));
+    assert!(!hlsl_field_ok(8, 12, 0)); // float2 + float3 straddles
+    assert!(!hlsl_field_ok(4, 16, 0));
+    assert!(hlsl_field_ok(32, 64, 0));
+    assert!(!hlsl_field_ok(0, 16, 4)); // float a[4] has 16 byte stride
+    assert!(!hlsl_field_ok(4, 32, 16));
+  }
+
+  /// test hlsl cbuffer parser
+  #[test]
+  fn test_hlsl() {
+    let h = Hlsl::load("./resource/shader_PS.hlsl", &[]).expect("hlsl");
+    let l = h.struct_of("DX_D3D11_CONST_LIGHT").expect("light");
+    assert_eq!(l.size, 112);
+    assert_eq!(l.member("Padding1").map(|m| (m.offset, m.size)), Some((4, 12)));
+    assert_eq!(l.member("Ambient").map(|m| m.offset), Some(80));
+    let c = h.cbuffer_at(0).expect("common");
+    assert_eq!((c.name.as_str(), c.size), ("cbD3D11_CONST_BUFFER_COMMON", 768));
+    let f = h.cbuffer("cbD3D11_CONST_BUFFER_PS_FILTER").expect("filter");
+    assert_eq!((f.slot, f.n()), (Some(3), 80));
+    let t = h.cbuffer("cb_Test").expect("test");
+    assert_eq!(t.member("g_Arr").map(|m| (m.offset, m.size)), Some((16, 64)));
+    assert_eq!((t.slot, t.n()), (Some(4), 5));
+    let cl = h.cbuffer("cb_CamLight").expect("camlight");
+    assert_eq!((cl.slot, cl.n()), (Some(8), 4));
+    crate::cbuffer! {
+      struct CamLight { cam_pos4: FLOAT4, cam_lat4: FLOAT4, r: [FLOAT4; 2] }
+    }
+    assert!(h.verify::<CamLight>("cb_CamLight").is_ok());
+    assert!(h.verify::<CamLight>("cb_Test").is_err());
+    let g = h.to_rust("cb_CamLight").expect("rust");
+    assert!(g.contains("pub struct CamLight {"));
+    assert!(g.contains("pub r: [FLOAT4; 2]"));
+    let h = Hlsl::parse("#define N (2 + 1)\ncbuffer c : register(b3) {
+      float a; float3 b; float2 c; float3 d; float e[N - 1]; float f;
+      row_major float3x2 m; float4x3 n = {0, 0}; }; // tail").expect("parse");
+    let c = h.cbuffer_at(3).expect("c");
+    let o: Vec<_> = c.members.iter().map(|m| (m.offset, m.size)).collect();
+    assert_eq!(o, vec![(0, 4), (4, 12), (16, 8), (32, 12), (48, 20), (68, 4),
+      (80, 40), (128, 48)]);
+    assert_eq!(c.size, 176);
+    assert!(h.to_rust("c").is_err()); // float e[2] has no Rust type
+    assert!(Hlsl::parse("cbuffer c { float4 a : packoffset(c0); };").is_err());
+    let v = Hlsl::load("./resource/shader_VS.hlsl", &[]).expect("vs");
+    let m = v.struct_of("DX_D3D11_VS_CONST_BUFFER_OTHERMATRIX").expect("om");
+    assert_eq!((m.members[1].offset, m.size), (192, 288));
+    assert_eq!(v.cbuffer_at(3).map(|c| c.size), Some(2592));
+    assert!(Hlsl::parse("cbuffer c { float a[0x7fffffffffffffff * 2]; };")
+      .is_err());
+    assert!(Hlsl::parse("cbuffer c { float a[0x7fffffffffffffff];\
+      float b[0x7fffffffffffffff]; };").is_err());
+    assert!(Hlsl::parse("cbuffer c { float a[-(-0x7fffffffffffffff - 1)]; };")
+      .is_err());
+  }
+
+  /// test ShaderProgram slots
+  #[test]
+  fn test_shader_program() {
+    let cb = |s| ConstantBuffer::<FLOAT4>::new(false, 1, 1, s);
+    let (ps, g) = (PixelShader{d: false, h: 2}, Graph{d: false, h: 3});
+    let vs = VertexShader{d: false, h: 4};
+    let p = ProgramDesc::new().pixel(&ps).constant(DX_SHADERTYPE_PIXEL, &cb(5))
+      .constant(DX_SHADERTYPE_PIXEL, &cb(6)).texture(0, &g).texture(1, &g);
+    assert!(p.build().is_ok());
+    let p = ProgramDesc::new().pixel(&ps).vertex(&vs)
+      .constant(DX_SHADERTYPE_PIXEL, &cb(5))
+      .constant(DX_SHADERTYPE_VERTEX, &cb(5));
+    assert!(p.build().is_ok());
+    let p = ProgramDesc::new().pixel(&ps).constant(DX_SHADERTYPE_PIXEL, &cb(5))
+      .constant_at(DX_SHADERTYPE_PIXEL, 5, &cb(6));
+    assert!(p.build().is_err());
+    let p = ProgramDesc::new().pixel(&ps).texture(2, &g).texture(2, &g);
+    assert!(p.build().is_err());
+    let p = ProgramDesc::new().pixel(&ps)
+      .constant(DX_SHADERTYPE_VERTEX, &cb(4));
+    assert!(p.build().is_err());
+    let p = ProgramDesc::new().pixel(&ps)
+      .constant(DX_SHADERTYPE_PIXEL, &cb(14));
+    assert!(p.build().is_err());
+    assert!(ProgramDesc::new().texture(0, &g).build().is_err());
+    let mut o = cb(5);
+    assert!(o.writable() && o.dirty());
+    let w = o.writer();
+    assert!(w.writable() && !o.writable() && !o.writer().writable());
+    let r = w.as_constant_buffer();
+    assert!(!r.writable() && r.dirty());
+    let mut r = std::panic::AssertUnwindSafe(r);
+    assert!(std::panic::catch_unwind(move || { r.map(); }).is_err());
+  }
+
+  /// test hot reload polling and shader swap
+  #[test]
+  fn test_hot_reload() {
+    let n = std::env::temp_dir().join(format!("dxlib_hot_{}.pso",
+      std::process::id()));
+    std::fs::write(&n, b"a").expect("write");
+    let n = format!("{}\0", n.to_str().expect("str"));
+    let mut hr = HotReload{interval: Duration::ZERO, ..HotReload::default()};
+    hr.watch(&n, ReloadKind::Pixel, 10);
+    hr.watch(&n, ReloadKind::Pixel, 10); // replaced
+    assert_eq!((hr.watches.len(), hr.poll()), (1, vec![]));
+    let f = std::fs::File::options().write(true)
+      .open(n.trim_end_matches('\0')).expect("open");
+    f.set_modified(SystemTime::now() + Duration::from_secs(5)).expect("mtime");
+    assert_eq!((hr.poll(), hr.poll()), (vec![0], vec![]));
+    hr.interval = Duration::from_secs(60);
+    f.set_modified(SystemTime::now() + Duration::from_secs(9)).expect("mtime");
+    assert_eq!(hr.poll(), vec![]); // within interval
+    std::fs::remove_file(n.trim_end_matches('\0')).expect("remove");
+    hr.unwatch(10);
+    assert!(hr.watches.is_empty());
+    swap_shader(-100, -101);
+    swap_shader(-101, -102);
+    assert_eq!((current_shader(-100), current_shader(-101)), (-102, -102));
+    assert_eq!(current_shader(-103), -103);
+    swap_shader(-102, -100); // deleted -100 is used again
+    assert_eq!((current_shader(-100), current_shader(-101)), (-100, -100));
+    release_shader(-100);
+    assert_eq!((current_shader(-101), current_shader(-102)), (-101, -102));
+  }
+
+  /// test rgba size
+  #[test]
+  fn test_rgba() {
+    assert_eq!(rgba_len(3, 2), Some(24));
+    assert_eq!((rgba_len(-1, 2), rgba_len(0, 0)), (None, Some(0)));
+    assert!(Graph::from_rgba(2, 2, &[0; 15]).is_err());
+    assert!(Graph::from_rgba(-2, -2, &[0; 16]).is_err());
+  }
+
+  /// test Batch2D vertices
+  #[test]
+  fn test_batch() {
+    let c = COLOR_U8::new(1, 2, 3, 4);
+    let mut b = Batch2D::triangles();
+    b.quad(0.0, 0.0, 2.0, 1.0, &c);
+    let p = b.vs.iter().map(|v| (v.pos.x, v.pos.y, v.uv.u, v.uv.v))
+      .collect::<Vec<_>>();
+    assert_eq!(p, vec![(0.0, 0.0, 0.0, 0.0), (2.0, 0.0, 1.0, 0.0),
+      (0.0, 1.0, 0.0, 1.0), (2.0, 0.0, 1.0, 0.0), (2.0, 1.0, 1.0, 1.0),
+      (0.0, 1.0, 0.0, 1.0)]);
+    for t in p.chunks_exact(3) { // CW on the screen (y down)
+      let (a, b, c) = (t[0], t[1], t[2]);
+      assert!((b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0) > 0.0);
+    }
+    assert!(b.vs.iter().all(|v| v.rhw == 1.0 && v.dif == c));
+    let (c0, c1) = (COLOR_U8::zeros(), c.clone());
+    let c2 = COLOR_U8::new(9, 9, 9, 9);
+    b.clear().tri_uv([(0.0, 0.0, 0.5, 0.5), (1.0, 0.0, 1.0, 0.5),
+      (0.0, 1.0, 0.5, 1.0)], [&c0, &c1, &c2]);
+    assert_eq!(b.vs.iter().map(|v| v.dif.clone()).collect::<Vec<_>>(),
+      vec![c0, c1, c2]);
+    assert_eq!((b.vs[1].uv.u, b.vs[2].uv.v), (1.0, 1.0));
+    let mut q = Batch2D::points();
+    q.pixel(3, 4, &c);
+    assert_eq!((q.len(), q.vs[0].pos.x, q.vs[0].pos.y), (1, 3.0, 4.0));
+    let mut t = Batch2D::triangles();
+    t.pixel(3, 4, &c);
+    assert_eq!((t.len(), t.vs[4].pos.x, t.vs[4].pos.y), (6, 4.0, 5.0));
+  }
+
+  /// test Filter Blend params
+  #[test]
+  fn test_filter_params() {
+    let f = Filter::Mono{cb: 1, cr: -2}.params();
+    assert_eq!(f, (DX_GRAPH_FILTER_MONO, [1, -2, 0, 0, 0, 0, 0, 0, 0, 0]));
+    let f = Filter::BrightClip{greater: false, param: 128, fill: true,
+      color: 0xff0000, alpha: 255}.params();
+    assert_eq!(f, (DX_GRAPH_FILTER_BRIGHT_CLIP,
+      [DX_CMP_LESS, 128, TRUE, 0xff0000, 255, 0, 0, 0, 0, 0]));
+    let f = Filter::Replacement{target: COLOR_U8::new(1, 2, 3, 4),
+      color: COLOR_U8::new(5, 6, 7, 8)}.params(); // b g r a
+    assert_eq!(f, (DX_GRAPH_FILTER_REPLACEMENT,
+      [3, 2, 1, 4, 7, 6, 5, 8, 0, 0]));
+    let b = Blend::RgbaSelectMix{r: 1, g: 2, b: 3, a: 4}.params();
+    assert_eq!(b, (DX_GRAPH_BLEND_RGBA_SELECT_MIX,
+      [1, 2, 3, 4, 0, 0, 0, 0, 0, 0]));
+    assert_eq!(Blend::Add.params(), (DX_GRAPH_BLEND_ADD, [0; 10]));
+  }
+
+  /// test ImageFormat by extension
+  #[test]
+  fn test_image_format() {
+    assert_eq!(ImageFormat::from_path("a.PNG\0"), Some(ImageFormat::Png(-1)));
+    assert_eq!(ImageFormat::from_path("d/b.jpeg"),
+      Some(ImageFormat::Jpeg(80, TRUE)));
+    assert_eq!(ImageFormat::from_path("c.Jpg"),
+      Some(ImageFormat::Jpeg(80, TRUE)));
+    assert_eq!(ImageFormat::from_path("e.bmp"), Some(ImageFormat::Bmp));
+    assert_eq!(ImageFormat::from_path("f.tga"), None);
+    assert_eq!(ImageFormat::from_path("png"), None); // no extension
+  }
+
+  /// test PostChain ping-pong
+  #[test]
+  fn test_ping_pong() {
+    assert_eq!(ping_pong(0, 3), vec![(0, 1), (1, 0), (0, 1)]);
+    assert_eq!(ping_pong(1, 2), vec![(1, 0), (0, 1)]); // after odd passes
+    assert!(ping_pong(0, 0).is_empty());
+    let p = ping_pong(0, 4);
+    assert!(p.windows(2).all(|w| w[0].1 == w[1].0)); // chained in order
+    assert_eq!(p.last().map(|l| l.1), Some(0));
+  }
+
+  /// test LightParamSub
+  #[test]
+  fn test_light_param() {
+    let c = COLOR_F::new(1.0, 0.5, 0.25, 1.0);
+    let l = LightParamSub::new(DX_LIGHTTYPE_SPOT, c.clone(), c.clone(),
+      COLOR_F::zeros(), VECTOR::new(0.0, 512.0, 0.0),
+      VECTOR::new(0.0, -1.0, 0.0));
+    assert_eq!((l.range, l.attenuation0, l.fall_off), (2000.0, 1.0, 1.0));
+    let l = l.range_atten(1000.0, 0.5, 0.25, 0.125).angle(1.5, 0.75);
+    let p = l.to_param();
+    assert_eq!((p.light_type, p.range, p.phi, p.theta),
+      (DX_LIGHTTYPE_SPOT, 1000.0, 1.5, 0.75));
+    assert_eq!((p.attenuation0, p.attenuation1, p.attenuation2),
+      (0.5, 0.25, 0.125));
+    let b = LightParamSub::from(&p);
+    assert_eq!((b.diffuse, b.position.y, b.direction.y), (c, 512.0, -1.0));
+    assert_eq!((b.range, b.phi, b.theta), (l.range, l.phi, l.theta));
+  }
+
   /// test screen
   #[test]
   fn test_screen() {